@@ -9,23 +9,46 @@ use anyhow::Result;
 use reqwest::StatusCode;
 use serde_json::Error;
 
+use crate::schedular::infeasibility::Conflict;
 use crate::schedular::scheduling_problem::test_run;
+use crate::schedular::settings::SolverCaps;
 
-use self::endpoints::{deparments, optimize};
+use self::endpoints::{
+    deparments, job_events, job_status, optimize, pareto, submit_job, verify_schedule,
+};
+use self::jobs::JobRegistry;
+use self::worker_pool::{WorkerPool, WorkerPoolError};
 
 pub mod endpoints;
+pub mod jobs;
+pub mod worker_pool;
 
 #[derive(Debug, thiserror::Error)]
 enum ApiError {
     #[error("internal server error")]
     InternalError,
 
-    #[error("bad request")]
-    BadClientData,
+    #[error("bad request: {0}")]
+    BadClientData(String),
+
+    #[error("infeasible: {0}")]
+    Infeasible(Conflict),
+
+    #[error("the time limit was reached before any schedule was found")]
+    TimeLimitReached,
+
+    #[error("server busy: {0}")]
+    Overloaded(String),
+
+    #[error("not found: {0}")]
+    NotFound(String),
 }
 
 impl error::ResponseError for ApiError {
     fn error_response(&self) -> HttpResponse {
+        if let ApiError::Infeasible(conflict) = self {
+            return HttpResponse::build(self.status_code()).json(conflict);
+        }
         HttpResponse::build(self.status_code())
             .insert_header(ContentType::html())
             .body(self.to_string())
@@ -34,15 +57,40 @@ impl error::ResponseError for ApiError {
     fn status_code(&self) -> StatusCode {
         match *self {
             ApiError::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
-            ApiError::BadClientData => StatusCode::BAD_REQUEST,
+            ApiError::BadClientData(_) => StatusCode::BAD_REQUEST,
+            ApiError::Infeasible(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::TimeLimitReached => StatusCode::GATEWAY_TIMEOUT,
+            ApiError::Overloaded(_) => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+        }
+    }
+}
+
+impl From<WorkerPoolError> for ApiError {
+    fn from(err: WorkerPoolError) -> Self {
+        match err {
+            WorkerPoolError::QueueFull => ApiError::Overloaded(err.to_string()),
+            WorkerPoolError::Panicked => ApiError::InternalError,
         }
     }
 }
 
 pub async fn run_server() -> Result<()> {
+    // shared by all http workers, so the limit holds for the whole server
+    let worker_pool = web::Data::new(WorkerPool::from_env()?);
+    let jobs = web::Data::new(JobRegistry::from_env()?);
+    let solver_caps = web::Data::new(SolverCaps::from_env()?);
     HttpServer::new(move || {
         App::new()
+            .app_data(worker_pool.clone())
+            .app_data(solver_caps.clone())
+            .app_data(jobs.clone())
             .service(optimize) //order here matters
+            .service(pareto)
+            .service(verify_schedule)
+            .service(submit_job)
+            .service(job_status)
+            .service(job_events)
             .service(deparments)
             .service(Files::new("/", "./frontend/dist").index_file("index.html"))
     })
@@ -1,24 +1,73 @@
 use actix_web::error::{ErrorInternalServerError, ErrorServiceUnavailable};
 use actix_web::get;
-use actix_web::{post, web::Json, Responder, Result};
-use itertools::Itertools;
+use actix_web::{
+    http::header::{CacheControl, CacheDirective},
+    post,
+    web::{self, Bytes, Json},
+    HttpResponse, Responder, Result,
+};
 use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
 use tracing::info;
 
 use crate::db_setup::connection;
-use crate::schedular::scheduling_problem::SchedulingProblem;
-use crate::schedular::settings::{ConstraintSettings, FilterSettings, SolutionObjective};
+use crate::schedular::course_selection::CourseSelection;
+use crate::schedular::scheduling_problem::{SchedulingProblem, SolutionSchedule, SolveProgress};
+use crate::schedular::settings::{
+    ConstraintSettings, CourseFilter, ObjectiveSettings, ParetoSettings, SolverCaps, SolverSettings,
+};
+use crate::schedular::solver::SolveLimits;
+use crate::schedular::verifier::{verify, Breach};
+use crate::schedular::SchedularError;
 use crate::scraper::organization::TumOrganization;
 
+use super::jobs::{JobEvent, JobRegistry, JobStatus};
+use super::worker_pool::WorkerPool;
 use super::ApiError;
 
 #[derive(Deserialize, Debug)]
 struct OptimizeRequest {
-    courses: Vec<String>,
-    curriculum: String,
-    semester: String,
+    #[serde(flatten)]
+    filter: CourseFilter,
     constraints: ConstraintSettings,
-    objective: SolutionObjective,
+    objective: ObjectiveSettings,
+    #[serde(flatten)]
+    solver: SolverSettings,
+}
+
+#[derive(Serialize, Debug)]
+struct JobCreated {
+    id: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct ParetoRequest {
+    #[serde(flatten)]
+    filter: CourseFilter,
+    constraints: ConstraintSettings,
+    #[serde(default)]
+    pareto: ParetoSettings,
+    #[serde(flatten)]
+    solver: SolverSettings,
+}
+
+/// A schedule to check, e.g. one returned by `/api/optimize` or one built by hand.
+#[derive(Deserialize, Debug)]
+struct VerifyRequest {
+    schedule: ScheduleToVerify,
+    #[serde(default)]
+    constraints: ConstraintSettings,
+}
+
+#[derive(Deserialize, Debug)]
+struct ScheduleToVerify {
+    course_selections: Vec<CourseSelection>,
+}
+
+#[derive(Serialize, Debug)]
+struct VerifyResponse {
+    valid: bool,
+    breaches: Vec<Breach>,
 }
 
 #[get("/api/departments")]
@@ -30,25 +79,197 @@ pub async fn deparments() -> Result<impl Responder> {
 }
 
 #[post("/api/optimize")]
-pub async fn optimize(optimize_request: Json<OptimizeRequest>) -> Result<impl Responder, ApiError> {
+pub async fn optimize(
+    optimize_request: Json<OptimizeRequest>,
+    worker_pool: web::Data<WorkerPool>,
+    solver_caps: web::Data<SolverCaps>,
+) -> Result<impl Responder, ApiError> {
     tracing::info!("Handling optimization request");
-    let mut scheduling_problem = SchedulingProblem::new();
-    let filter_settings = FilterSettings {
-        courses: Some(&optimize_request.courses),
-        semester: Some(&optimize_request.semester),
-        excluded_courses: None,
-        faculties: None,
-        curriculum: Some(&optimize_request.curriculum),
-    };
-
-    let solutions = scheduling_problem.solve(
-        filter_settings,
-        &optimize_request.constraints,
-        &optimize_request.objective,
+    let optimize_request = optimize_request.into_inner();
+    let limits = optimize_request
+        .solver
+        .limits(&solver_caps)
+        .map_err(api_error)?;
+    // actix drops the handler when the client disconnects, which cancels the solve
+    // (HiGHS only notices this between re-solves)
+    let _cancel_on_disconnect = limits.cancel_on_drop();
+
+    // waiting for a worker does not count against the time limit
+    let solutions = worker_pool
+        .run(move || optimize_request.solve(SchedulingProblem::new(), limits.start_now()))
+        .await?;
+    solutions.map(Json).map_err(api_error)
+}
+
+/// Checks a schedule against the hard constraints of the settings without solving anything.
+#[post("/api/verify")]
+pub async fn verify_schedule(verify_request: Json<VerifyRequest>) -> Result<impl Responder> {
+    let breaches = verify(
+        &verify_request.schedule.course_selections,
+        &verify_request.constraints,
     );
+    Ok(Json(VerifyResponse {
+        valid: breaches.is_empty(),
+        breaches,
+    }))
+}
+
+/// Queues an optimization and answers with the id of the job right away.
+#[post("/api/jobs")]
+pub async fn submit_job(
+    optimize_request: Json<OptimizeRequest>,
+    worker_pool: web::Data<WorkerPool>,
+    solver_caps: web::Data<SolverCaps>,
+    jobs: web::Data<JobRegistry>,
+) -> Result<impl Responder, ApiError> {
+    tracing::info!("Queueing optimization job");
+    let optimize_request = optimize_request.into_inner();
+    let limits = optimize_request
+        .solver
+        .limits(&solver_caps)
+        .map_err(api_error)?;
+    // a full queue is rejected right away instead of failing the accepted job
+    let slot = worker_pool.reserve()?;
+    let id = jobs.create();
+
+    let job_id = id.clone();
+    actix_web::rt::spawn(async move {
+        let worker_jobs = jobs.clone();
+        let worker_id = job_id.clone();
+        let result = worker_pool
+            .run_reserved(slot, move || {
+                worker_jobs.set_status(&worker_id, JobStatus::Running);
+                let mut scheduling_problem = SchedulingProblem::new();
+                let progress_jobs = worker_jobs.clone();
+                scheduling_problem.set_progress_observer(move |progress: SolveProgress| {
+                    progress_jobs.publish(&worker_id, progress)
+                });
+                optimize_request.solve(scheduling_problem, limits.start_now())
+            })
+            .await;
+        let status = match result {
+            Ok(Ok(schedules)) => JobStatus::Finished { schedules },
+            Ok(Err(SchedularError::Infeasible(conflict))) => JobStatus::Failed {
+                error: conflict.to_string(),
+                conflict: Some(conflict),
+            },
+            Ok(Err(err)) => JobStatus::Failed {
+                error: err.to_string(),
+                conflict: None,
+            },
+            Err(err) => JobStatus::Failed {
+                error: err.to_string(),
+                conflict: None,
+            },
+        };
+        jobs.set_status(&job_id, status);
+    });
+    Ok(HttpResponse::Accepted().json(JobCreated { id }))
+}
+
+#[get("/api/jobs/{id}")]
+pub async fn job_status(
+    id: web::Path<String>,
+    jobs: web::Data<JobRegistry>,
+) -> Result<impl Responder, ApiError> {
+    jobs.status(&id)
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound(format!("job {}", id)))
+}
+
+/// Streams the status changes and the solver progress of a job as server-sent events,
+/// starting with its current status. The stream ends with the job.
+#[get("/api/jobs/{id}/events")]
+pub async fn job_events(
+    id: web::Path<String>,
+    jobs: web::Data<JobRegistry>,
+) -> Result<HttpResponse, ApiError> {
+    let (status, receiver) = jobs
+        .subscribe(&id)
+        .ok_or_else(|| ApiError::NotFound(format!("job {}", id)))?;
+    let first = JobEvent::Status(status);
+    let events = futures::stream::unfold(Some((Some(first), receiver)), |state| async move {
+        let (pending, mut receiver) = state?;
+        let event = match pending {
+            Some(event) => event,
+            None => loop {
+                match receiver.recv().await {
+                    Ok(event) => break event,
+                    // a slow client misses some progress, the final status is sent last
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return None,
+                }
+            },
+        };
+        let next = (!event.is_final()).then_some((None, receiver));
+        Some((Ok::<_, actix_web::Error>(Bytes::from(event.to_sse())), next))
+    });
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(CacheControl(vec![CacheDirective::NoCache]))
+        .streaming(events))
+}
+
+#[post("/api/pareto")]
+pub async fn pareto(
+    pareto_request: Json<ParetoRequest>,
+    worker_pool: web::Data<WorkerPool>,
+    solver_caps: web::Data<SolverCaps>,
+) -> Result<impl Responder, ApiError> {
+    tracing::info!("Handling pareto front request");
+    let pareto_request = pareto_request.into_inner();
+    let limits = pareto_request
+        .solver
+        .limits(&solver_caps)
+        .map_err(api_error)?;
+    let _cancel_on_disconnect = limits.cancel_on_drop();
+
+    let front = worker_pool
+        .run(move || {
+            let limits = limits.start_now();
+            let mut scheduling_problem = SchedulingProblem::new();
+            let solver = pareto_request.solver.solver(limits);
+            pareto_request
+                .filter
+                .with_filter_settings(|filter_settings| {
+                    scheduling_problem.pareto_front_with(
+                        solver.as_ref(),
+                        filter_settings,
+                        &pareto_request.constraints,
+                        &pareto_request.pareto,
+                    )
+                })
+        })
+        .await?;
+    front.map(Json).map_err(api_error)
+}
+
+impl OptimizeRequest {
+    fn solve(
+        &self,
+        mut scheduling_problem: SchedulingProblem,
+        limits: SolveLimits,
+    ) -> Result<Vec<SolutionSchedule>, SchedularError> {
+        let solver = self.solver.solver(limits);
+        self.filter.with_filter_settings(|filter_settings| {
+            scheduling_problem.solve_with(
+                solver.as_ref(),
+                filter_settings,
+                &self.constraints,
+                &self.objective,
+            )
+        })
+    }
+}
 
-    match solutions {
-        Ok(solutions) => Ok(Json(solutions)),
-        Err(_) => return Err(ApiError::InternalError),
+fn api_error(err: SchedularError) -> ApiError {
+    match err {
+        SchedularError::InvalidConstraint(_)
+        | SchedularError::RequiredCourseUnavailable(_)
+        | SchedularError::UnknownSelection(_)
+        | SchedularError::InvalidSolverSettings(_) => ApiError::BadClientData(err.to_string()),
+        SchedularError::Infeasible(conflict) => ApiError::Infeasible(conflict),
+        SchedularError::TimeLimitReached => ApiError::TimeLimitReached,
+        _ => ApiError::InternalError,
     }
 }
@@ -0,0 +1,232 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use itertools::Itertools;
+use serde::Serialize;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use crate::schedular::{
+    infeasibility::Conflict,
+    scheduling_problem::{SolutionSchedule, SolveProgress},
+    settings::env_or,
+    SchedularError,
+};
+
+/// Events buffered per subscriber before slow clients start missing progress updates
+const EVENT_CAPACITY: usize = 64;
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Finished {
+        schedules: Vec<SolutionSchedule>,
+    },
+    Failed {
+        error: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        conflict: Option<Conflict>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub enum JobEvent {
+    Status(JobStatus),
+    Progress(SolveProgress),
+}
+
+struct Job {
+    status: JobStatus,
+    events: broadcast::Sender<JobEvent>,
+    finished_at: Option<Instant>,
+}
+
+/// Optimization jobs of the server, finished ones are kept for `retention` and at most
+/// `max_finished` of them. Unfinished jobs are bounded by the worker queue.
+pub struct JobRegistry {
+    jobs: Mutex<HashMap<String, Job>>,
+    retention: Duration,
+    max_finished: usize,
+}
+
+impl JobStatus {
+    pub fn is_done(&self) -> bool {
+        matches!(self, JobStatus::Finished { .. } | JobStatus::Failed { .. })
+    }
+}
+
+impl JobEvent {
+    pub fn is_final(&self) -> bool {
+        matches!(self, JobEvent::Status(status) if status.is_done())
+    }
+
+    /// Formats the event as a server-sent event named `status` or `progress`.
+    pub fn to_sse(&self) -> String {
+        let (name, data) = match self {
+            JobEvent::Status(status) => ("status", serde_json::to_string(status)),
+            JobEvent::Progress(progress) => ("progress", serde_json::to_string(progress)),
+        };
+        let data = data.expect("events should serialize to json");
+        format!("event: {}\ndata: {}\n\n", name, data)
+    }
+}
+
+impl JobRegistry {
+    pub fn new(retention: Duration, max_finished: usize) -> Self {
+        Self {
+            jobs: Mutex::new(HashMap::new()),
+            retention,
+            max_finished,
+        }
+    }
+
+    /// Keeps finished jobs for `JOB_RETENTION_SECS`, an hour by default, and at most
+    /// `JOB_MAX_FINISHED` of them, 1000 by default.
+    pub fn from_env() -> Result<Self, SchedularError> {
+        Ok(Self::new(
+            Duration::from_secs(env_or("JOB_RETENTION_SECS", 3600)?),
+            env_or("JOB_MAX_FINISHED", 1000)?,
+        ))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Job>> {
+        self.jobs
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Registers a queued job and returns its id. Expired jobs and the oldest finished
+    /// ones beyond `max_finished` are removed on the way.
+    pub fn create(&self) -> String {
+        let mut jobs = self.lock();
+        jobs.retain(|_, job| match job.finished_at {
+            Some(finished_at) => finished_at.elapsed() < self.retention,
+            None => true,
+        });
+        let finished = jobs
+            .iter()
+            .filter_map(|(id, job)| Some((job.finished_at?, id.clone())))
+            .sorted()
+            .collect_vec();
+        let num_removed = finished.len().saturating_sub(self.max_finished);
+        for (_, id) in finished.into_iter().take(num_removed) {
+            jobs.remove(&id);
+        }
+        let id = Uuid::new_v4().to_string();
+        let (events, _) = broadcast::channel(EVENT_CAPACITY);
+        jobs.insert(
+            id.clone(),
+            Job {
+                status: JobStatus::Queued,
+                events,
+                finished_at: None,
+            },
+        );
+        id
+    }
+
+    pub fn status(&self, id: &str) -> Option<JobStatus> {
+        self.lock().get(id).map(|job| job.status.clone())
+    }
+
+    /// The current status together with a receiver for all later events.
+    pub fn subscribe(&self, id: &str) -> Option<(JobStatus, broadcast::Receiver<JobEvent>)> {
+        self.lock()
+            .get(id)
+            .map(|job| (job.status.clone(), job.events.subscribe()))
+    }
+
+    pub fn set_status(&self, id: &str, status: JobStatus) {
+        let mut jobs = self.lock();
+        let Some(job) = jobs.get_mut(id) else {
+            return;
+        };
+        if status.is_done() {
+            job.finished_at = Some(Instant::now());
+        }
+        job.status = status.clone();
+        // nobody listening is fine, the status can still be polled
+        job.events.send(JobEvent::Status(status)).ok();
+    }
+
+    pub fn publish(&self, id: &str, progress: SolveProgress) {
+        if let Some(job) = self.lock().get(id) {
+            job.events.send(JobEvent::Progress(progress)).ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{JobRegistry, JobStatus};
+
+    #[test]
+    fn test_job_lifecycle() {
+        let registry = JobRegistry::new(Duration::from_secs(60), 10);
+        let id = registry.create();
+        assert!(matches!(registry.status(&id), Some(JobStatus::Queued)));
+        assert!(registry.status("unknown").is_none());
+
+        let (_, mut events) = registry.subscribe(&id).expect("the job should exist");
+        registry.set_status(&id, JobStatus::Running);
+        registry.set_status(&id, JobStatus::Finished { schedules: vec![] });
+
+        let running = events
+            .try_recv()
+            .expect("should receive the running status");
+        assert!(!running.is_final());
+        assert_eq!(
+            running.to_sse(),
+            "event: status\ndata: {\"status\":\"running\"}\n\n"
+        );
+        assert!(events
+            .try_recv()
+            .expect("should receive the result")
+            .is_final());
+    }
+
+    #[test]
+    fn test_finished_jobs_expire() {
+        let registry = JobRegistry::new(Duration::ZERO, 10);
+        let finished = registry.create();
+        registry.set_status(
+            &finished,
+            JobStatus::Failed {
+                error: "infeasible".to_string(),
+                conflict: None,
+            },
+        );
+        let running = registry.create();
+        registry.set_status(&running, JobStatus::Running);
+
+        registry.create();
+        assert!(registry.status(&finished).is_none());
+        assert!(matches!(
+            registry.status(&running),
+            Some(JobStatus::Running)
+        ));
+    }
+
+    #[test]
+    fn test_oldest_finished_jobs_are_removed() {
+        let registry = JobRegistry::new(Duration::from_secs(60), 1);
+        let oldest = registry.create();
+        let newest = registry.create();
+        let running = registry.create();
+        registry.set_status(&oldest, JobStatus::Finished { schedules: vec![] });
+        registry.set_status(&newest, JobStatus::Finished { schedules: vec![] });
+        registry.set_status(&running, JobStatus::Running);
+
+        registry.create();
+        assert!(registry.status(&oldest).is_none());
+        assert!(registry.status(&newest).is_some());
+        assert!(registry.status(&running).is_some());
+    }
+}
@@ -0,0 +1,133 @@
+use std::{
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+use tokio::sync::Semaphore;
+
+use crate::schedular::{settings::env_or, SchedularError};
+
+/// Runs solves on blocking threads, at most `max_workers` at a time. Further requests
+/// wait for a free worker, unless `max_queued` of them are waiting already.
+pub struct WorkerPool {
+    workers: Arc<Semaphore>,
+    max_queued: usize,
+    queued: Arc<AtomicUsize>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WorkerPoolError {
+    #[error("all solver workers are busy and the queue is full")]
+    QueueFull,
+    #[error("the solver worker panicked")]
+    Panicked,
+}
+
+/// A place in the queue, left when dropped, also if the waiting request is dropped.
+pub struct QueueSlot(Arc<AtomicUsize>);
+
+impl Drop for QueueSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl WorkerPool {
+    pub fn new(max_workers: usize, max_queued: usize) -> Self {
+        Self {
+            workers: Arc::new(Semaphore::new(max_workers)),
+            max_queued,
+            queued: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Configured by `SOLVER_MAX_WORKERS`, defaulting to the number of cores,
+    /// and `SOLVER_MAX_QUEUED`.
+    pub fn from_env() -> Result<Self, SchedularError> {
+        let cores = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        let max_workers = env_or("SOLVER_MAX_WORKERS", cores)?;
+        if max_workers == 0 {
+            return Err(SchedularError::InvalidConfiguration(
+                "SOLVER_MAX_WORKERS has to be at least 1".to_string(),
+            ));
+        }
+        Ok(Self::new(max_workers, env_or("SOLVER_MAX_QUEUED", 32)?))
+    }
+
+    /// Takes a place in the queue, so a request can be rejected before it is accepted.
+    pub fn reserve(&self) -> Result<QueueSlot, WorkerPoolError> {
+        if self.queued.fetch_add(1, Ordering::SeqCst) >= self.max_queued {
+            self.queued.fetch_sub(1, Ordering::SeqCst);
+            return Err(WorkerPoolError::QueueFull);
+        }
+        Ok(QueueSlot(self.queued.clone()))
+    }
+
+    pub async fn run<F, T>(&self, task: F) -> Result<T, WorkerPoolError>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let slot = self.reserve()?;
+        self.run_reserved(slot, task).await
+    }
+
+    /// Runs the task once a worker is free, leaving the queue through `slot`.
+    pub async fn run_reserved<F, T>(&self, slot: QueueSlot, task: F) -> Result<T, WorkerPoolError>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let worker = {
+            let _slot = slot;
+            self.workers
+                .clone()
+                .acquire_owned()
+                .await
+                .expect("the worker semaphore is never closed")
+        };
+        // the worker is only released once the task is done, even if the request is gone
+        tokio::task::spawn_blocking(move || {
+            let _worker = worker;
+            task()
+        })
+        .await
+        .map_err(|_| WorkerPoolError::Panicked)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::mpsc;
+
+    use super::{WorkerPool, WorkerPoolError};
+
+    #[tokio::test]
+    async fn test_full_queue_is_rejected() {
+        let pool = WorkerPool::new(1, 1);
+        let (release, released) = mpsc::channel::<()>();
+        let busy = pool.run(move || released.recv().ok());
+        let waiting = pool.run(|| 1);
+        let rejected = pool.run(|| 2);
+        let (busy, waiting, rejected, _) = tokio::join!(busy, waiting, rejected, async move {
+            tokio::task::yield_now().await;
+            release.send(()).ok();
+        });
+        assert_eq!(busy.unwrap(), Some(()));
+        assert_eq!(waiting.unwrap(), 1);
+        assert!(matches!(rejected, Err(WorkerPoolError::QueueFull)));
+    }
+
+    #[tokio::test]
+    async fn test_reserved_slot_counts_as_queued() {
+        let pool = WorkerPool::new(1, 1);
+        let slot = pool.reserve().expect("the queue should be empty");
+        assert!(matches!(pool.reserve(), Err(WorkerPoolError::QueueFull)));
+        assert_eq!(pool.run_reserved(slot, || 1).await.unwrap(), 1);
+        assert!(pool.reserve().is_ok());
+    }
+}
@@ -3,11 +3,15 @@ use clap::{Parser, Subcommand};
 use crate::{
     api::run_server,
     meilisearch::init_melisearch,
-    schedular::scheduling_problem::test_run,
+    schedular::{
+        benchmark::{benchmark_run, pareto_run},
+        scheduling_problem::test_run,
+    },
     scraper::{aquire_curriculum_data, aquire_lecture_data},
 };
 use anyhow::Result;
 use dotenv::dotenv;
+use std::path::PathBuf;
 
 pub mod api;
 pub mod db_setup;
@@ -36,6 +40,25 @@ enum RunMode {
         #[arg(long)]
         semester: String,
     },
+    /// Prints the trade-off between attendance days and ECTS credits for a course list
+    Pareto {
+        #[arg(long)]
+        semester: String,
+        #[arg(long)]
+        curriculum: String,
+        #[arg(long, value_delimiter = ',')]
+        courses: Vec<String>,
+        #[arg(long)]
+        min_num_ects: Option<i32>,
+        /// Contact hour bounds per day to sweep as well
+        #[arg(long, value_delimiter = ',')]
+        max_hours_per_day: Vec<f64>,
+    },
+    /// Compares the quarter hour and the exact overlap model on an optimize request
+    Benchmark {
+        /// Optimize request as json, e.g. resources/maxects_example.json
+        request: PathBuf,
+    },
     Debug,
 }
 
@@ -61,6 +84,26 @@ async fn main() -> Result<()> {
             tracing::info!("Starting web server");
             run_server().await?;
         }
+        RunMode::Pareto {
+            semester,
+            curriculum,
+            courses,
+            min_num_ects,
+            max_hours_per_day,
+        } => {
+            tracing::info!("Computing the pareto front of days and ECTS");
+            pareto_run(
+                &semester,
+                &curriculum,
+                &courses,
+                min_num_ects,
+                max_hours_per_day,
+            )?;
+        }
+        RunMode::Benchmark { request } => {
+            tracing::info!("Benchmarking the overlap constraints");
+            benchmark_run(&request)?;
+        }
         RunMode::Debug => {
             tracing::info!("Running Schedular Testrun");
             test_run()?;
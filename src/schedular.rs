@@ -1,13 +1,42 @@
 use thiserror::Error;
 
+pub mod benchmark;
 pub mod course_selection;
+pub mod course_source;
+pub mod infeasibility;
 pub mod scheduling_problem;
 pub mod settings;
+pub mod solver;
+pub mod verifier;
 
 pub const WEEKDAYS: [&str; 5] = ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday"];
 
 #[derive(Error, Debug)]
 pub enum SchedularError {
-    #[error("Failed to add vairiable {0}")]
-    VariableAddingError(#[from] grb::Error),
+    #[cfg(feature = "gurobi")]
+    #[error("Gurobi failed to solve the model: {0}")]
+    GurobiError(#[from] grb::Error),
+    #[cfg(feature = "highs")]
+    #[error("HiGHS failed to solve the model: {0}")]
+    HighsError(#[from] good_lp::ResolutionError),
+    #[error("Invalid constraint settings: {0}")]
+    InvalidConstraint(String),
+    #[error("Required course `{0}` is not offered with the given filters")]
+    RequiredCourseUnavailable(String),
+    #[error("Course selection `{0}` is not offered with the given filters")]
+    UnknownSelection(String),
+    #[error("The constraints cannot be satisfied: {0}")]
+    Infeasible(infeasibility::Conflict),
+    #[error("Invalid server configuration: {0}")]
+    InvalidConfiguration(String),
+    #[error("Invalid solver settings: {0}")]
+    InvalidSolverSettings(String),
+    #[error("The time limit was reached before any schedule was found")]
+    TimeLimitReached,
+    #[error("The solve was cancelled")]
+    Cancelled,
+    #[error("Could not load the courses: {0}")]
+    CourseSource(String),
+    #[error("The solver only returned schedules that break constraints: {0}")]
+    UnverifiedSchedule(String),
 }
@@ -0,0 +1,113 @@
+use std::{fmt, fs::File, path::Path};
+
+use serde::Deserialize;
+
+use super::{
+    scheduling_problem::{ModelBenchmark, SchedulingProblem},
+    settings::{
+        ConstraintSettings, CourseFilter, FilterSettings, ObjectiveSettings, ParetoSettings,
+        SolverCaps, SolverSettings,
+    },
+    solver::SolveLimits,
+    SchedularError,
+};
+
+pub fn pareto_run(
+    semester: &str,
+    curriculum: &str,
+    courses: &Vec<String>,
+    min_num_ects: Option<i32>,
+    max_hours_per_day: Vec<f64>,
+) -> Result<(), SchedularError> {
+    let mut scheduling_problem = SchedulingProblem::new();
+    let filters = FilterSettings {
+        semester: Some(semester),
+        excluded_courses: None,
+        required_courses: None,
+        preferences: None,
+        courses: Some(courses),
+        faculties: None,
+        curriculum: Some(curriculum),
+    };
+    let constraints = ConstraintSettings {
+        min_num_ects,
+        ..Default::default()
+    };
+    let pareto_settings = ParetoSettings {
+        max_hours_per_day: Some(max_hours_per_day),
+    };
+
+    let front = scheduling_problem.pareto_front(
+        filters,
+        &constraints,
+        &pareto_settings,
+        SolveLimits::default(),
+    )?;
+    for schedule in front.iter() {
+        println!(
+            "{} days, at most {:.1} hours a day: {} ECTS with {}",
+            schedule.num_weekdays,
+            schedule.max_daily_minutes() as f64 / 60.,
+            schedule.total_ects,
+            schedule.subjects.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// An optimize request saved as json, e.g. resources/maxects_example.json
+#[derive(Deserialize, Debug)]
+struct BenchmarkRequest {
+    #[serde(flatten)]
+    filter: CourseFilter,
+    constraints: ConstraintSettings,
+    objective: ObjectiveSettings,
+    #[serde(flatten)]
+    solver: SolverSettings,
+}
+
+/// Prints model size and solve time of an optimize request saved as json, once with the
+/// former quarter hour overlap constraints and once with the exact ones.
+pub fn benchmark_run(request_path: &Path) -> anyhow::Result<()> {
+    let request: BenchmarkRequest = serde_json::from_reader(File::open(request_path)?)?;
+    let limits = request.solver.limits(&SolverCaps::from_env()?)?;
+    let benchmark = request.filter.with_filter_settings(|filter_settings| {
+        SchedulingProblem::new().benchmark(
+            &request.solver,
+            limits,
+            filter_settings,
+            &request.constraints,
+            &request.objective,
+        )
+    })?;
+    println!("{}", benchmark);
+    Ok(())
+}
+
+impl fmt::Display for ModelBenchmark {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<14}{:>8}{:>13}{:>10}{:>12}{:>11}",
+            "model", "vars", "constraints", "overlaps", "solve time", "objective"
+        )?;
+        for (name, measurement) in [
+            ("quarter hours", &self.quarter_hours),
+            ("cliques", &self.cliques),
+        ] {
+            writeln!(
+                f,
+                "{:<14}{:>8}{:>13}{:>10}{:>11.3}s{:>11}",
+                name,
+                measurement.num_vars,
+                measurement.num_constraints,
+                measurement.num_overlap_constraints,
+                measurement.solve_time.as_secs_f64(),
+                measurement
+                    .objective_value
+                    .map_or("-".to_string(), |value| format!("{:.2}", value)),
+            )?;
+        }
+        Ok(())
+    }
+}
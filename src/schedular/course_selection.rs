@@ -3,7 +3,8 @@ use itertools::Itertools;
 
 use diesel::result;
 use diesel::PgConnection;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 use crate::schema::lecture;
 use crate::scraper::appointment::SingleAppointment;
@@ -11,16 +12,56 @@ use crate::scraper::lecture::Lecture;
 
 use super::settings::FilterSettings;
 
-#[derive(Debug, Serialize, Clone)]
+/// Fields that only describe a selection may be left out when it is deserialized,
+/// e.g. in a hand-built schedule.
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CourseSelection {
+    /// Derived from the subject, the lecture ids and the exercise group, stable across requests
+    #[serde(default)]
+    pub id: String,
     pub subject: String,
+    /// Distinguishes the selections of the same subject, e.g. by their exercise group
+    #[serde(default)]
+    pub variant: usize,
+    #[serde(default)]
     pub name_en: String,
     pub appointments: Vec<SingleAppointment>,
+    #[serde(default)]
     pub faculty: String,
     pub ects: f64,
+    /// How much the subject is wanted, shared by all of its variants
+    #[serde(default = "default_preference")]
+    pub preference: f64,
+}
+
+pub const DEFAULT_PREFERENCE: f64 = 1.;
+
+fn default_preference() -> f64 {
+    DEFAULT_PREFERENCE
 }
 
 impl CourseSelection {
+    /// Identifies a selection by its subject, the ids of its teaching lectures and its
+    /// exercise appointment, e.g. `MA4800/950701+950702/950703@Monday-14:00`. An exercise
+    /// group meeting several times a week shares its id between the appointments.
+    pub fn identity(subject: &str, teaching: &[&Lecture], exercise: Option<&Lecture>) -> String {
+        let teaching_ids = teaching
+            .iter()
+            .map(|lecture| lecture.id.as_str())
+            .sorted()
+            .dedup()
+            .join("+");
+        let exercise = exercise.map_or(String::new(), |lecture| {
+            format!(
+                "{}@{}-{}",
+                lecture.id,
+                lecture.weekday,
+                lecture.start_time.format("%H:%M")
+            )
+        });
+        format!("{}/{}/{}", subject, teaching_ids, exercise)
+    }
+
     pub fn takes_place_on(&self, weekday: &String) -> bool {
         self.appointments.iter().any(|a| a.weekday == *weekday)
     }
@@ -32,6 +73,15 @@ impl CourseSelection {
             .unique()
     }
 
+    /// Sets the preference of every selection whose subject has been rated.
+    pub fn apply_preferences(selections: &mut [Self], preferences: &HashMap<String, f64>) {
+        for selection in selections.iter_mut() {
+            if let Some(&preference) = preferences.get(&selection.subject) {
+                selection.preference = preference;
+            }
+        }
+    }
+
     pub fn addmissiable_lectures(
         conn: &mut PgConnection,
         filters: FilterSettings,
@@ -53,6 +103,11 @@ impl CourseSelection {
         }
 
         if let Some(cour) = filters.courses {
+            let cour = cour
+                .iter()
+                .chain(filters.required_courses.into_iter().flatten())
+                .cloned()
+                .collect_vec();
             lectures = lectures.filter(lecture::subject.eq_any(cour));
         }
 
@@ -93,27 +148,34 @@ impl CourseSelection {
         let faculty = lec[0].organization.to_owned();
         let appointments = lec.iter().map(|l| l.appointment()).collect_vec();
         vec![Self {
+            id: Self::identity(&subject, lec, None),
             subject,
+            variant: 0,
             name_en,
             appointments,
             ects: f64::ceil(*ects),
             faculty,
+            preference: DEFAULT_PREFERENCE,
         }]
     }
 
     fn from_exercise_lectures(lec: &[&Lecture], ects: f64) -> Vec<Self> {
         lec.iter()
-            .map(|l| {
+            .enumerate()
+            .map(|(variant, l)| {
                 let subject = l.subject.to_owned();
                 let name_en = l.name_en.to_owned();
                 let appointment = l.appointment();
                 let faculty = l.organization.to_owned();
                 Self {
+                    id: Self::identity(&subject, &[], Some(l)),
                     subject,
+                    variant,
                     name_en,
                     appointments: vec![appointment],
                     ects,
                     faculty,
+                    preference: DEFAULT_PREFERENCE,
                 }
             })
             .collect_vec()
@@ -126,15 +188,18 @@ impl CourseSelection {
         let faculty = &lec[0].organization;
         let teaching_appointments = lec.iter().map(|l| l.appointment()).collect_vec();
 
-        for ex in exer.iter() {
+        for (variant, ex) in exer.iter().enumerate() {
             let mut appointments = teaching_appointments.clone();
             appointments.push(ex.appointment());
             let selection = Self {
+                id: Self::identity(subject, lec, Some(ex)),
                 subject: subject.to_owned(),
+                variant,
                 name_en: name_en.to_owned(),
                 appointments,
                 ects: *ects,
                 faculty: faculty.to_owned(),
+                preference: DEFAULT_PREFERENCE,
             };
             selections.push(selection);
         }
@@ -202,6 +267,8 @@ mod test {
             courses: None,
             semester: Some("23W"),
             excluded_courses: None,
+            required_courses: None,
+            preferences: None,
             faculties: None, //Some("IN".to_string()),
             curriculum: None,
         };
@@ -226,6 +293,13 @@ mod test {
         assert_eq!(selections.len(), 5);
         assert_eq!(first_selections.iter().count(), 2);
         assert_eq!(second_selection.iter().count(), 3);
+        assert_eq!(
+            second_selection
+                .iter()
+                .map(|selection| selection.variant)
+                .collect_vec(),
+            vec![0, 1, 2]
+        );
         first_selections.iter().for_each(|selection| {
             assert_eq!(selection.appointments.len(), 3);
             assert_eq!(selection.ects, 8.);
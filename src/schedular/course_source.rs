@@ -0,0 +1,181 @@
+use itertools::Itertools;
+use std::{fs::File, path::Path};
+
+use crate::{db_setup::connection, scraper::lecture::Lecture};
+
+use super::{course_selection::CourseSelection, settings::FilterSettings, SchedularError};
+
+/// Course types that can be part of a schedule
+const SCHEDULED_COURSE_TYPES: [&str; 3] = ["VO", "VI", "UE"];
+
+/// Where the scheduler loads the admissible lectures of a request from.
+pub trait CourseSource {
+    /// The lectures matching the filters, ordered by subject with the teaching lectures
+    /// before the exercises of the same subject.
+    fn lectures(&self, filter_settings: FilterSettings) -> Result<Vec<Lecture>, SchedularError>;
+}
+
+/// The lectures scraped into the database.
+#[derive(Debug, Default)]
+pub struct PostgresCourseSource;
+
+/// Lectures held in memory, e.g. loaded from a json fixture. The filters are applied
+/// like the database query does.
+#[derive(Debug, Default)]
+pub struct InMemoryCourseSource {
+    lectures: Vec<Lecture>,
+}
+
+impl CourseSource for PostgresCourseSource {
+    fn lectures(&self, filter_settings: FilterSettings) -> Result<Vec<Lecture>, SchedularError> {
+        let conn =
+            &mut connection().map_err(|err| SchedularError::CourseSource(err.to_string()))?;
+        CourseSelection::addmissiable_lectures(conn, filter_settings)
+            .map_err(|err| SchedularError::CourseSource(err.to_string()))
+    }
+}
+
+impl InMemoryCourseSource {
+    pub fn new(lectures: Vec<Lecture>) -> Self {
+        Self { lectures }
+    }
+
+    /// Reads a json array of lectures as they are stored in the database.
+    pub fn from_json_file(path: &Path) -> Result<Self, SchedularError> {
+        let file = File::open(path)
+            .map_err(|err| SchedularError::CourseSource(format!("{}: {}", path.display(), err)))?;
+        let lectures = serde_json::from_reader(file)
+            .map_err(|err| SchedularError::CourseSource(format!("{}: {}", path.display(), err)))?;
+        Ok(Self::new(lectures))
+    }
+
+    fn admits(filter_settings: &FilterSettings, lecture: &Lecture) -> bool {
+        let requested = filter_settings.courses.map(|courses| {
+            courses
+                .iter()
+                .chain(filter_settings.required_courses.into_iter().flatten())
+                .any(|course| *course == lecture.subject)
+        });
+        filter_settings
+            .semester
+            .iter()
+            .all(|semester| lecture.semester == *semester)
+            && filter_settings
+                .curriculum
+                .iter()
+                .all(|curriculum| lecture.curriculum == *curriculum)
+            && filter_settings
+                .faculties
+                .iter()
+                .all(|faculties| faculties.contains(&lecture.organization))
+            && !filter_settings
+                .excluded_courses
+                .is_some_and(|excluded| excluded.contains(&lecture.subject))
+            && requested.unwrap_or(true)
+            && SCHEDULED_COURSE_TYPES.contains(&lecture.course_type.as_str())
+    }
+}
+
+impl CourseSource for InMemoryCourseSource {
+    fn lectures(&self, filter_settings: FilterSettings) -> Result<Vec<Lecture>, SchedularError> {
+        let lectures = self
+            .lectures
+            .iter()
+            .filter(|lecture| Self::admits(&filter_settings, lecture))
+            // lectures that only differ in their position are adjacent after sorting
+            .sorted_by(|a, b| {
+                a.subject
+                    .cmp(&b.subject)
+                    .then_with(|| b.course_type.cmp(&a.course_type))
+                    .then_with(|| a.id.cmp(&b.id))
+                    .then_with(|| a.weekday.cmp(&b.weekday))
+                    .then_with(|| a.start_time.cmp(&b.start_time))
+            })
+            .dedup()
+            .cloned()
+            .collect();
+        Ok(lectures)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use itertools::Itertools;
+
+    use crate::{schedular::settings::FilterSettings, scraper::lecture::Lecture};
+
+    use super::{CourseSource, InMemoryCourseSource};
+
+    const FIXTURE: &str = "resources/fixtures/lectures.json";
+
+    fn no_filters<'a>() -> FilterSettings<'a> {
+        FilterSettings {
+            semester: None,
+            courses: None,
+            excluded_courses: None,
+            required_courses: None,
+            preferences: None,
+            faculties: None,
+            curriculum: None,
+        }
+    }
+
+    #[test]
+    fn test_fixture_filters() {
+        let source = InMemoryCourseSource::from_json_file(Path::new(FIXTURE))
+            .expect("should read the fixture");
+        let subjects = |lectures: Vec<Lecture>| {
+            lectures
+                .into_iter()
+                .map(|lecture| lecture.subject)
+                .dedup()
+                .collect_vec()
+        };
+
+        let lectures = source
+            .lectures(FilterSettings {
+                semester: Some("24S"),
+                curriculum: Some("5244"),
+                ..no_filters()
+            })
+            .unwrap();
+        assert!(lectures
+            .iter()
+            .all(|lecture| lecture.semester == "24S" && lecture.course_type != "SE"));
+        // ordered like the database query, teaching lectures first
+        assert!(lectures
+            .windows(2)
+            .all(|pair| pair[0].subject < pair[1].subject
+                || pair[0].subject == pair[1].subject
+                    && pair[0].course_type >= pair[1].course_type));
+        let all_subjects = subjects(lectures);
+        assert!(!all_subjects.contains(&"MA9999".to_string()));
+
+        let courses = vec!["MA2003".to_string(), "MA3080".to_string()];
+        let required = vec!["IN2010".to_string()];
+        let excluded = vec!["MA3080".to_string()];
+        let lectures = source
+            .lectures(FilterSettings {
+                semester: Some("24S"),
+                curriculum: Some("5244"),
+                courses: Some(&courses),
+                required_courses: Some(&required),
+                excluded_courses: Some(&excluded),
+                ..no_filters()
+            })
+            .unwrap();
+        assert_eq!(subjects(lectures), vec!["IN2010", "MA2003"]);
+
+        let faculties = vec!["IN".to_string()];
+        let lectures = source
+            .lectures(FilterSettings {
+                semester: Some("24S"),
+                faculties: Some(&faculties),
+                ..no_filters()
+            })
+            .unwrap();
+        assert!(lectures.iter().all(|lecture| lecture.organization == "IN"));
+    }
+}
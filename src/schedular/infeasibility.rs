@@ -0,0 +1,171 @@
+use std::{collections::HashSet, fmt, ops::Range};
+
+use itertools::Itertools;
+use serde::Serialize;
+
+use super::{
+    solver::{LinExpr, Model, ModelSense, Solver},
+    SchedularError,
+};
+
+/// The model constraints that stem from a single setting, e.g. one blocked slot.
+#[derive(Debug, Clone)]
+pub struct ConstraintGroup {
+    /// Name of the setting in the request, e.g. `blocked_slots`
+    pub setting: &'static str,
+    pub description: String,
+    pub constraints: Range<usize>,
+    /// The quantity the setting bounds, used to report what is achievable without it
+    pub bound: Option<(LinExpr, ModelSense)>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BoundSense {
+    Max,
+    Min,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct AchievableBound {
+    pub constraint: String,
+    pub sense: BoundSense,
+    pub value: f64,
+}
+
+/// A minimal set of settings that cannot be satisfied together.
+#[derive(Serialize, Debug, Clone)]
+pub struct Conflict {
+    pub message: String,
+    pub constraints: Vec<String>,
+    pub achievable: Vec<AchievableBound>,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl fmt::Display for BoundSense {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BoundSense::Max => write!(f, "max"),
+            BoundSense::Min => write!(f, "min"),
+        }
+    }
+}
+
+impl ConstraintGroup {
+    pub fn new(setting: &'static str, description: String, constraints: Range<usize>) -> Self {
+        Self {
+            setting,
+            description,
+            constraints,
+            bound: None,
+        }
+    }
+}
+
+fn is_feasible(model: &Model, solver: &dyn Solver) -> Result<bool, SchedularError> {
+    Ok(!solver.solve(model, 1)?.is_empty())
+}
+
+/// Finds an irreducible conflicting subset of `groups` with a deletion filter: a group is
+/// dropped for good whenever the model stays infeasible without it. Constraints outside
+/// of any group, like the ones preventing overlaps, are always kept.
+pub fn explain_infeasibility(
+    model: &Model,
+    groups: &[ConstraintGroup],
+    solver: &dyn Solver,
+) -> Result<Conflict, SchedularError> {
+    let mut feasibility_model = model.clone();
+    feasibility_model.set_objective(LinExpr::new(), ModelSense::Minimize);
+
+    let mut removed: HashSet<usize> = HashSet::new();
+    let mut culprits = vec![];
+    for group in groups {
+        removed.extend(group.constraints.clone());
+        if is_feasible(&feasibility_model.without_constraints(&removed), solver)? {
+            for index in group.constraints.clone() {
+                removed.remove(&index);
+            }
+            culprits.push(group);
+        }
+    }
+
+    let mut achievable = vec![];
+    for group in culprits.iter() {
+        let Some((expr, sense)) = &group.bound else {
+            continue;
+        };
+        let mut relaxed_removed = removed.clone();
+        relaxed_removed.extend(group.constraints.clone());
+        let mut relaxed_model = feasibility_model.without_constraints(&relaxed_removed);
+        relaxed_model.set_objective(expr.clone(), *sense);
+        if let Some(solution) = solver.solve(&relaxed_model, 1)?.into_iter().next() {
+            achievable.push(AchievableBound {
+                constraint: group.description.clone(),
+                sense: match sense {
+                    ModelSense::Maximize => BoundSense::Max,
+                    ModelSense::Minimize => BoundSense::Min,
+                },
+                value: solution.objective_value(),
+            });
+        }
+    }
+
+    // settings with an achievable value read best at the front of the message
+    let constraints = culprits
+        .iter()
+        .sorted_by_key(|group| group.bound.is_none())
+        .map(|group| group.description.clone())
+        .collect_vec();
+    Ok(Conflict {
+        message: describe(&constraints, &achievable),
+        constraints,
+        achievable,
+    })
+}
+
+fn describe(constraints: &[String], achievable: &[AchievableBound]) -> String {
+    let mut message = match constraints {
+        [] => "the courses cannot be scheduled without overlaps".to_string(),
+        [single] => format!("{} cannot be satisfied", single),
+        [first, others @ ..] => format!("{} is unreachable with {}", first, others.join(", ")),
+    };
+    for bound in achievable {
+        if constraints.first() == Some(&bound.constraint) {
+            message += &format!("; {} achievable is {}", bound.sense, bound.value);
+        } else {
+            message += &format!(
+                "; {} achievable for {} is {}",
+                bound.sense, bound.constraint, bound.value
+            );
+        }
+    }
+    message
+}
+
+#[cfg(test)]
+mod test {
+    use super::{describe, AchievableBound, BoundSense};
+
+    #[test]
+    fn test_describe_conflict() {
+        let constraints = vec!["min_num_ects 30".to_string(), "max_num_days 2".to_string()];
+        let achievable = vec![AchievableBound {
+            constraint: "min_num_ects 30".to_string(),
+            sense: BoundSense::Max,
+            value: 24.,
+        }];
+        assert_eq!(
+            describe(&constraints, &achievable),
+            "min_num_ects 30 is unreachable with max_num_days 2; max achievable is 24"
+        );
+        assert_eq!(
+            describe(&["required course MA4800".to_string()], &[]),
+            "required course MA4800 cannot be satisfied"
+        );
+    }
+}
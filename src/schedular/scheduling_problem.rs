@@ -1,79 +1,418 @@
-use crate::{
-    db_setup::connection, schedular::settings::FilterSettings,
-    scraper::appointment::SingleAppointment,
-};
+use crate::{schedular::settings::FilterSettings, scraper::appointment::SingleAppointment};
 
 use super::{
     course_selection::CourseSelection,
-    settings::{ConstraintSettings, SolutionObjective},
+    course_source::{CourseSource, PostgresCourseSource},
+    infeasibility::{explain_infeasibility, ConstraintGroup},
+    settings::{
+        ConstraintSettings, DayWindow, EctsRange, FreeHours, ObjectiveSettings, ParetoSettings,
+        SolutionObjective, SolverSettings, TimeSlot, WeightedObjective,
+    },
+    solver::{
+        default_solver, relative_gap, ConstrSense, LinExpr, Model, ModelSense, Solution,
+        SolveLimits, Solver, SolverProgress, Var, VarType,
+    },
+    verifier::verify,
     WEEKDAYS,
 };
-use grb::{
-    attribute::{ModelDoubleAttr::ObjVal, ModelIntAttr::SolCount},
-    parameter::IntParam::{PoolSearchMode, PoolSolutions, SolutionLimit, SolutionNumber},
-    prelude::*,
-};
 use itertools::Itertools;
 use serde::Serialize;
-use std::collections::HashMap;
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap, HashSet},
+    ops::Range,
+    time::{Duration, Instant},
+};
 
-use chrono::Duration;
-use grb::{c, expr::LinExpr};
+use chrono::NaiveTime;
 
 use super::SchedularError;
 
 pub struct SchedulingProblem {
+    course_source: Box<dyn CourseSource + Send>,
     model: Model,
-    vars: Vec<Var>,
+    /// The course selection every course variable decides on
+    selections: BTreeMap<Var, CourseSelection>,
+    selection_vars: HashMap<String, Vec<Var>>,
+    subject_variants: HashMap<String, Vec<Var>>,
+    subject_vars: HashMap<String, Var>,
     weekday_exprs: HashMap<String, LinExpr>,
     on_weekday_vars: HashMap<String, Vec<Var>>,
-    interval_exprs: HashMap<String, LinExpr>,
+    weekday_vars: HashMap<String, Var>,
+    amount_weekdays: LinExpr,
+    sessions: Vec<Session>,
     amount_ects: LinExpr,
+    amount_preference: LinExpr,
     faculties: HashMap<String, LinExpr>,
+    constraint_groups: Vec<ConstraintGroup>,
+    soft_penalties: HashMap<String, f64>,
+    soft_violations: Vec<SoftViolation>,
+    penalty: LinExpr,
+    max_num_solutions: usize,
+    min_subject_distance: Option<usize>,
+    blocked_slots: Vec<TimeSlot>,
+    free_hours: Option<FreeHours>,
+    target_ects: Option<EctsRange>,
+    ects_deviation: Option<LinExpr>,
+    campus_time: Option<CampusTimeExprs>,
+    objective_components: Vec<(SolutionObjective, LinExpr)>,
+    objective_levels: Vec<LinExpr>,
+    progress_observer: Option<Box<dyn Fn(SolveProgress) + Send>>,
+}
+
+/// Intermediate results of a running solve, as pushed to clients of a job.
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SolveProgress {
+    Incumbent {
+        schedule: Box<SolutionSchedule>,
+    },
+    /// Objective and bound of the lexicographic `level` currently optimized
+    Bound {
+        level: usize,
+        objective: f64,
+        bound: f64,
+        gap: f64,
+    },
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Clone)]
 pub struct SolutionSchedule {
-    objective_value: f64,
-    total_ects: f64,
+    status: SolutionStatus,
+    /// Relative gap to the solver's bound, for solvers that report one with their solutions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gap: Option<f64>,
+    objective_values: Vec<ObjectiveValue>,
+    pub total_ects: f64,
+    num_courses: usize,
+    pub num_weekdays: usize,
+    pub subjects: Vec<String>,
     course_selections: Vec<CourseSelection>,
+    blocked_slots: Vec<TimeSlot>,
+    free_days: Vec<String>,
+    free_slots: Vec<TimeSlot>,
+    days: Vec<DaySummary>,
+    violations: Vec<Violation>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Violation {
+    constraint: String,
+    amount: f64,
+    message: String,
+}
+
+/// Whether the solver proved a schedule optimal (up to the requested gap)
+/// or stopped at the time limit with the best schedule found so far.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SolutionStatus {
+    Optimal,
+    BestFound,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ObjectiveValue {
+    objective: SolutionObjective,
+    value: f64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DaySummary {
+    weekday: String,
+    span_minutes: i64,
+    idle_minutes: i64,
+}
+
+/// How much the constraints of a soft setting are violated.
+struct SoftViolation {
+    setting: &'static str,
+    description: String,
+    amount: LinExpr,
+}
+
+/// Size and solve time of the scheduling model with one way of preventing overlaps.
+#[derive(Debug, Clone)]
+pub struct ModelMeasurement {
+    pub num_vars: usize,
+    pub num_constraints: usize,
+    pub num_overlap_constraints: usize,
+    pub solve_time: Duration,
+    pub objective_value: Option<f64>,
+}
+
+/// The exact overlap model compared to the former one with a constraint per quarter hour.
+#[derive(Debug, Clone)]
+pub struct ModelBenchmark {
+    pub quarter_hours: ModelMeasurement,
+    pub cliques: ModelMeasurement,
+}
+
+/// A single appointment of a course selection, taking place if its selection's variable is one.
+struct Session {
+    weekday: String,
+    from: NaiveTime,
+    to: NaiveTime,
+    var: Var,
+}
+
+/// Expressions counting the minutes spent on campus and the idle ones among them.
+#[derive(Clone)]
+struct CampusTimeExprs {
+    span: LinExpr,
+    idle: LinExpr,
+}
+
+const LEXICOGRAPHIC_TOLERANCE: f64 = 1e-6;
+const SOFT_SETTINGS: [&str; 12] = [
+    "min_num_ects",
+    "max_num_ects",
+    "max_num_days",
+    "max_courses_per_faculty",
+    "blocked_slots",
+    "day_windows",
+    "max_hours_per_day",
+    "min_free_days",
+    "free_hours",
+    "required_courses",
+    "locked_selections",
+    "banned_selections",
+];
+
+/// The maximal sets of pairwise overlapping `(from, to)` intervals as indices, found by
+/// sweeping over the start and end times. Intervals that only touch do not overlap.
+fn overlap_cliques(intervals: &[(NaiveTime, NaiveTime)]) -> Vec<Vec<usize>> {
+    // ends sort before starts at the same time
+    let events = intervals
+        .iter()
+        .enumerate()
+        .filter(|(_, (from, to))| from < to)
+        .flat_map(|(index, &(from, to))| [(to, false, index), (from, true, index)])
+        .sorted()
+        .collect_vec();
+    let mut cliques = vec![];
+    let mut active = vec![];
+    let mut grown = false;
+    for (_, is_start, index) in events {
+        if is_start {
+            active.push(index);
+            grown = true;
+        } else {
+            // the active set is maximal at the first end after a start
+            if grown {
+                cliques.push(active.iter().copied().sorted().collect_vec());
+                grown = false;
+            }
+            active.retain(|&other| other != index);
+        }
+    }
+    cliques
+}
+
+impl SolutionSchedule {
+    pub fn max_daily_minutes(&self) -> i64 {
+        self.days
+            .iter()
+            .map(|day| day.span_minutes - day.idle_minutes)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Needs no more days, and if asked no more daily hours, while getting at least as many ECTS.
+    fn dominates(&self, other: &SolutionSchedule, with_hours: bool) -> bool {
+        self.num_weekdays <= other.num_weekdays
+            && (!with_hours || self.max_daily_minutes() <= other.max_daily_minutes())
+            && self.total_ects >= other.total_ects
+    }
+}
+
+/// Keeps the schedules no other schedule dominates, of equal ones only the first.
+fn non_dominated(schedules: Vec<SolutionSchedule>, with_hours: bool) -> Vec<SolutionSchedule> {
+    let is_dominated = schedules
+        .iter()
+        .enumerate()
+        .map(|(index, schedule)| {
+            schedules.iter().enumerate().any(|(other_index, other)| {
+                other_index != index
+                    && other.dominates(schedule, with_hours)
+                    && (!schedule.dominates(other, with_hours) || other_index < index)
+            })
+        })
+        .collect_vec();
+    schedules
+        .into_iter()
+        .zip(is_dominated)
+        .filter(|(_, dominated)| !dominated)
+        .map(|(schedule, _)| schedule)
+        .sorted_by_key(|schedule| (schedule.num_weekdays, schedule.max_daily_minutes()))
+        .collect()
+}
+
+/// Converts the slack of a soft setting into the setting's unit and describes it.
+fn describe_violation(setting: &str, slack: f64) -> (f64, String) {
+    let round = |value: f64| (value * 100.).round() / 100.;
+    let plural = |amount: f64, noun: &str| {
+        if amount == 1. {
+            format!("1 {}", noun)
+        } else {
+            format!("{} {}s", amount, noun)
+        }
+    };
+    match setting {
+        "blocked_slots" | "day_windows" => {
+            let minutes = round(slack);
+            let place = if setting == "blocked_slots" {
+                "in the blocked slot"
+            } else {
+                "outside the day window"
+            };
+            (minutes, format!("{} {}", plural(minutes, "minute"), place))
+        }
+        "max_hours_per_day" | "free_hours" => {
+            let hours = round(slack / 60.);
+            let message = if setting == "max_hours_per_day" {
+                format!("{} too many", plural(hours, "hour"))
+            } else {
+                format!("{} short", plural(hours, "free hour"))
+            };
+            (hours, message)
+        }
+        _ => {
+            let amount = round(slack);
+            let message = match setting {
+                "min_num_ects" => format!("{} ECTS short", amount),
+                "max_num_ects" => format!("{} ECTS too many", amount),
+                "max_num_days" => plural(amount, "extra day"),
+                "min_free_days" => format!("{} short", plural(amount, "free day")),
+                "max_courses_per_faculty" => plural(amount, "extra course"),
+                "required_courses" | "locked_selections" => "not selected".to_string(),
+                "banned_selections" => "selected".to_string(),
+                _ => format!("violated by {}", amount),
+            };
+            (amount, message)
+        }
+    }
+}
+
+fn minutes_outside_window(session: &Session, window: &DayWindow) -> i64 {
+    if window
+        .weekday
+        .as_ref()
+        .is_some_and(|weekday| *weekday != session.weekday)
+    {
+        return 0;
+    }
+    let before = window.earliest_start.map_or(0, |earliest| {
+        (earliest.min(session.to) - session.from)
+            .num_minutes()
+            .max(0)
+    });
+    let after = window.latest_end.map_or(0, |latest| {
+        (session.to - latest.max(session.from)).num_minutes().max(0)
+    });
+    before + after
+}
+
+fn free_intervals(
+    from: NaiveTime,
+    to: NaiveTime,
+    mut busy: Vec<(NaiveTime, NaiveTime)>,
+) -> Vec<(NaiveTime, NaiveTime)> {
+    busy.sort();
+    let mut intervals = vec![];
+    let mut start = from;
+    for (busy_from, busy_to) in busy {
+        if busy_to <= start {
+            continue;
+        }
+        if busy_from >= to {
+            break;
+        }
+        if busy_from > start {
+            intervals.push((start, busy_from));
+        }
+        start = busy_to;
+    }
+    if start < to {
+        intervals.push((start, to));
+    }
+    intervals
+}
+
+fn overlap_minutes(a: (NaiveTime, NaiveTime), b: (NaiveTime, NaiveTime)) -> i64 {
+    let start = a.0.max(b.0);
+    let end = a.1.min(b.1);
+    if start < end {
+        (end - start).num_minutes()
+    } else {
+        0
+    }
 }
 
 impl SchedulingProblem {
+    /// A problem with the courses of the database.
     pub fn new() -> Self {
-        let model = Model::new("schedular").expect("should be able to create grb model");
+        Self::with_course_source(PostgresCourseSource)
+    }
+
+    pub fn with_course_source<S: CourseSource + Send + 'static>(course_source: S) -> Self {
+        let model = Model::new("schedular");
         Self {
+            course_source: Box::new(course_source),
             model,
-            vars: vec![],
+            selections: BTreeMap::new(),
+            selection_vars: HashMap::new(),
+            subject_variants: HashMap::new(),
+            subject_vars: HashMap::new(),
             weekday_exprs: HashMap::new(),
             on_weekday_vars: HashMap::new(),
-            interval_exprs: HashMap::new(),
+            weekday_vars: HashMap::new(),
+            amount_weekdays: LinExpr::new(),
+            sessions: vec![],
             amount_ects: LinExpr::new(),
+            amount_preference: LinExpr::new(),
             faculties: HashMap::new(),
+            constraint_groups: vec![],
+            soft_penalties: HashMap::new(),
+            soft_violations: vec![],
+            penalty: LinExpr::new(),
+            max_num_solutions: 1,
+            min_subject_distance: None,
+            blocked_slots: vec![],
+            free_hours: None,
+            target_ects: None,
+            ects_deviation: None,
+            campus_time: None,
+            objective_components: vec![],
+            objective_levels: vec![],
+            progress_observer: None,
         }
     }
 
-    pub fn add_courses<I: IntoIterator<Item = CourseSelection>>(
-        &mut self,
-        subject_aps: I,
-    ) -> Result<(), SchedularError> {
+    /// Reports incumbents and bound updates of all following solves to `observer`.
+    pub fn set_progress_observer<F: Fn(SolveProgress) + Send + 'static>(&mut self, observer: F) {
+        self.progress_observer = Some(Box::new(observer));
+    }
+
+    pub fn add_courses<I: IntoIterator<Item = CourseSelection>>(&mut self, subject_aps: I) {
         for (var_num, subject_ap) in subject_aps.into_iter().enumerate() {
-            self.add_course(&subject_ap, var_num)?;
+            self.add_course(&subject_ap, var_num);
         }
-        Ok(())
     }
 
-    pub fn add_course(
-        &mut self,
-        course_selection: &CourseSelection,
-        schedule_num: usize,
-    ) -> Result<(), SchedularError> {
+    pub fn add_course(&mut self, course_selection: &CourseSelection, schedule_num: usize) {
         let course_var_name = format!("{}_v{}", course_selection.subject, schedule_num);
-        let course_var = self
-            .model
-            .add_var(&course_var_name, Binary, 0., 0., 1., [])?;
+        let course_var = self.model.add_binary(&course_var_name);
 
-        self.vars.push(course_var);
+        self.selection_vars
+            .entry(course_selection.id.clone())
+            .or_default()
+            .push(course_var);
+        self.selections.insert(course_var, course_selection.clone());
+        self.subject_variants
+            .entry(course_selection.subject.clone())
+            .or_default()
+            .push(course_var);
         self.add_faculty(course_var, course_selection);
         for appointment in course_selection.appointments.iter() {
             self.add_session(course_var, appointment);
@@ -84,7 +423,8 @@ impl SchedulingProblem {
         }
 
         self.amount_ects.add_term(course_selection.ects, course_var);
-        Ok(())
+        self.amount_preference
+            .add_term(course_selection.preference, course_var);
     }
 
     fn add_faculty(&mut self, course_var: Var, course_selection: &CourseSelection) {
@@ -99,20 +439,83 @@ impl SchedulingProblem {
     }
 
     fn add_session(&mut self, appointment_var: Var, appointment: &SingleAppointment) {
-        let mut time_point = appointment.from;
-        while time_point < appointment.to {
-            let constraint_name = format!("{}_{}", appointment.weekday, time_point);
-            if let Some(expr) = self.interval_exprs.get_mut(&constraint_name) {
-                expr.add_term(1.0, appointment_var);
-            } else {
-                let mut expr = LinExpr::new();
-                expr.add_term(1.0, appointment_var);
-                self.interval_exprs.insert(constraint_name, expr);
+        self.sessions.push(Session {
+            weekday: appointment.weekday.clone(),
+            from: appointment.from,
+            to: appointment.to,
+            var: appointment_var,
+        });
+    }
+
+    /// At most one of every set of pairwise overlapping sessions can take place. One
+    /// constraint per maximal set suffices, which is exact for arbitrary start and end times.
+    fn add_conflict_constraints(&mut self) {
+        let sessions_per_day = self
+            .sessions
+            .iter()
+            .into_group_map_by(|session| session.weekday.clone());
+        let mut conflicts = vec![];
+        for (weekday, sessions) in sessions_per_day
+            .into_iter()
+            .sorted_by(|(a, _), (b, _)| a.cmp(b))
+        {
+            let intervals = sessions
+                .iter()
+                .map(|session| (session.from, session.to))
+                .collect_vec();
+            for clique in overlap_cliques(&intervals) {
+                if clique.len() < 2 {
+                    continue;
+                }
+                let latest_start = clique.iter().map(|&index| intervals[index].0).max();
+                let name = format!(
+                    "conflict_{}_{}",
+                    weekday,
+                    latest_start.map_or(String::new(), |time| time.format("%H%M").to_string())
+                );
+                let expr = LinExpr::sum(clique.iter().map(|&index| &sessions[index].var));
+                conflicts.push((name, expr));
             }
-            time_point += Duration::minutes(15);
+        }
+        for (name, expr) in conflicts {
+            self.model.add_constr(&name, expr, ConstrSense::Less, 1.);
         }
     }
 
+    /// The current model with the overlap constraints replaced by the former ones, which
+    /// keyed every session by the quarter hours from its start on. Only used for benchmarks.
+    fn quarter_hour_model(&self) -> Model {
+        let conflicts: HashSet<usize> = self
+            .model
+            .constraints()
+            .iter()
+            .enumerate()
+            .filter(|(_, constr)| constr.name.starts_with("conflict_"))
+            .map(|(index, _)| index)
+            .collect();
+        let mut model = self.model.without_constraints(&conflicts);
+        let mut buckets: BTreeMap<(&String, NaiveTime), LinExpr> = BTreeMap::new();
+        for session in self.sessions.iter() {
+            let minutes = (session.to - session.from).num_minutes();
+            for offset in (0..minutes).step_by(15) {
+                let time = session.from + chrono::Duration::minutes(offset);
+                buckets
+                    .entry((&session.weekday, time))
+                    .or_default()
+                    .add_term(1., session.var);
+            }
+        }
+        for ((weekday, time), expr) in buckets {
+            model.add_constr(
+                &format!("{}_{}", weekday, time),
+                expr,
+                ConstrSense::Less,
+                1.,
+            );
+        }
+        model
+    }
+
     fn add_weekday(&mut self, session_var: Var, weekday: String) {
         if let Some(expr) = self.weekday_exprs.get_mut(&weekday) {
             expr.add_term(1.0, session_var);
@@ -128,130 +531,1204 @@ impl SchedulingProblem {
         }
     }
 
+    /// Adds a binary per subject that is one exactly if one of its variants is selected.
+    /// At most one variant of each subject can be part of a schedule.
+    pub fn add_subject_indicators(&mut self) {
+        for (subject, variants) in self
+            .subject_variants
+            .iter()
+            .sorted_by_key(|(subject, _)| *subject)
+        {
+            let subject_var = self.model.add_binary(&format!("{}_s", subject));
+            let mut one_variant_expr = LinExpr::sum(variants.iter());
+            one_variant_expr.add_term(-1., subject_var);
+            self.model.add_constr(
+                &format!("{}_one_variant", subject),
+                one_variant_expr,
+                ConstrSense::Equal,
+                0.,
+            );
+            self.subject_vars.insert(subject.clone(), subject_var);
+            let mut group = variants.clone();
+            group.push(subject_var);
+            self.model.add_decision_group(group);
+        }
+    }
+
+    /// Adds a binary per weekday that is one exactly if any selected course takes place that day.
+    pub fn add_weekday_indicators(&mut self) {
+        let weekdays = self
+            .on_weekday_vars
+            .keys()
+            .cloned()
+            .sorted_by_key(|weekday| {
+                WEEKDAYS
+                    .iter()
+                    .position(|w| w == weekday)
+                    .unwrap_or(WEEKDAYS.len())
+            })
+            .collect_vec();
+        for weekday in weekdays {
+            let weekday_var = self.model.add_binary(&format!("{}_v", weekday));
+            let mut is_off_expr = self.weekday_exprs[&weekday].clone();
+            is_off_expr.add_term(-1., weekday_var);
+            self.model.add_constr(
+                &format!("{}_is_off", weekday),
+                is_off_expr,
+                ConstrSense::Greater,
+                0.,
+            );
+            for (num, on_this_day_var) in self.on_weekday_vars[&weekday].iter().enumerate() {
+                let mut is_on_expr = LinExpr::new();
+                is_on_expr.add_term(1., *on_this_day_var);
+                is_on_expr.add_term(-1., weekday_var);
+                self.model.add_constr(
+                    &format!("{}_is_on_{}", weekday, num),
+                    is_on_expr,
+                    ConstrSense::Less,
+                    0.,
+                );
+            }
+            self.amount_weekdays.add_term(1., weekday_var);
+            self.weekday_vars.insert(weekday, weekday_var);
+        }
+    }
+
     pub fn add_constraints(
         &mut self,
         constraints: &ConstraintSettings,
     ) -> Result<(), SchedularError> {
-        let interval_constraints = self
-            .interval_exprs
-            .iter()
-            .map(|(name, expr)| (name, c!(expr.clone() <= 1)));
-        for (constr, expr) in interval_constraints {
-            self.model.add_constr(constr, expr)?;
+        self.soft_penalties = constraints.soft_constraints.clone().unwrap_or_default();
+        for (setting, penalty) in self.soft_penalties.iter() {
+            if !SOFT_SETTINGS.contains(&setting.as_str()) {
+                return Err(SchedularError::InvalidConstraint(format!(
+                    "`{}` cannot be a soft constraint",
+                    setting
+                )));
+            }
+            if !penalty.is_finite() || *penalty <= 0. {
+                return Err(SchedularError::InvalidConstraint(format!(
+                    "penalty of soft constraint `{}` has to be a positive number",
+                    setting
+                )));
+            }
         }
 
-        let solution_num = constraints.max_num_solutions.unwrap_or(1);
-        self.model.set_param(PoolSolutions, solution_num)?;
+        self.blocked_slots = constraints.blocked_slots.clone().unwrap_or_default();
+        for slot in self.blocked_slots.iter() {
+            if !WEEKDAYS.contains(&slot.weekday.as_str()) {
+                return Err(SchedularError::InvalidConstraint(format!(
+                    "blocked slot has unknown weekday `{}`",
+                    slot.weekday
+                )));
+            }
+            if slot.from >= slot.to {
+                return Err(SchedularError::InvalidConstraint(format!(
+                    "blocked slot on {} has to start before it ends",
+                    slot.weekday
+                )));
+            }
+        }
 
-        if let Some(min_ects) = constraints.min_num_ects {
+        let day_windows = constraints.day_windows.clone().unwrap_or_default();
+        for window in day_windows.iter() {
+            if let Some(weekday) = &window.weekday {
+                if !WEEKDAYS.contains(&weekday.as_str()) {
+                    return Err(SchedularError::InvalidConstraint(format!(
+                        "day window has unknown weekday `{}`",
+                        weekday
+                    )));
+                }
+            }
+            if let (Some(earliest), Some(latest)) = (window.earliest_start, window.latest_end) {
+                if earliest >= latest {
+                    return Err(SchedularError::InvalidConstraint(format!(
+                        "day window earliest start {} has to be before its latest end {}",
+                        earliest, latest
+                    )));
+                }
+            }
+        }
+
+        self.add_conflict_constraints();
+        for slot in self.blocked_slots.clone() {
+            let mut occupied = LinExpr::new();
+            for session in self.sessions.iter().filter(|s| s.weekday == slot.weekday) {
+                let minutes = overlap_minutes((session.from, session.to), (slot.from, slot.to));
+                if minutes > 0 {
+                    occupied.add_term(minutes as f64, session.var);
+                }
+            }
+            let name = format!("blocked_{}_{}", slot.weekday, slot.from);
             self.model
-                .add_constr("min_ects", c!(self.amount_ects.clone() >= min_ects))?;
+                .add_constr(&name, occupied, ConstrSense::Less, 0.);
+            self.push_last_constr_group(
+                "blocked_slots",
+                format!(
+                    "blocked slot {} {}-{}",
+                    slot.weekday,
+                    slot.from.format("%H:%M"),
+                    slot.to.format("%H:%M")
+                ),
+            );
+        }
+        for (num, window) in day_windows.iter().enumerate() {
+            let mut occupied = LinExpr::new();
+            for session in self.sessions.iter() {
+                let minutes = minutes_outside_window(session, window);
+                if minutes > 0 {
+                    occupied.add_term(minutes as f64, session.var);
+                }
+            }
+            self.model.add_constr(
+                &format!("day_window_{}", num),
+                occupied,
+                ConstrSense::Less,
+                0.,
+            );
+            self.push_last_constr_group(
+                "day_windows",
+                format!(
+                    "day window {} {}-{}",
+                    window.weekday.as_deref().unwrap_or("every day"),
+                    window
+                        .earliest_start
+                        .map(|time| time.format("%H:%M").to_string())
+                        .unwrap_or_default(),
+                    window
+                        .latest_end
+                        .map(|time| time.format("%H:%M").to_string())
+                        .unwrap_or_default()
+                ),
+            );
+        }
+
+        if let Some(max_hours) = constraints.max_hours_per_day {
+            let constraints = self.add_max_hours_per_day(max_hours)?;
+            self.constraint_groups.push(ConstraintGroup::new(
+                "max_hours_per_day",
+                format!("max_hours_per_day {}", max_hours),
+                constraints,
+            ));
+        }
+
+        self.max_num_solutions = constraints.max_num_solutions.unwrap_or(1).max(1) as usize;
+        if let Some(distance) = constraints.min_subject_distance {
+            if distance < 1 {
+                return Err(SchedularError::InvalidConstraint(
+                    "min_subject_distance has to be at least 1".to_string(),
+                ));
+            }
+            self.min_subject_distance = Some(distance as usize);
+        }
+
+        if let (Some(min_ects), Some(max_ects)) =
+            (constraints.min_num_ects, constraints.max_num_ects)
+        {
+            if min_ects > max_ects {
+                return Err(SchedularError::InvalidConstraint(format!(
+                    "min_num_ects {} is larger than max_num_ects {}",
+                    min_ects, max_ects
+                )));
+            }
+        }
+        if let Some(min_ects) = constraints.min_num_ects {
+            self.model.add_constr(
+                "min_ects",
+                self.amount_ects.clone(),
+                ConstrSense::Greater,
+                min_ects as f64,
+            );
+            self.push_last_constr_group("min_num_ects", format!("min_num_ects {}", min_ects))
+                .bound = Some((self.amount_ects.clone(), ModelSense::Maximize));
         }
+        if let Some(max_ects) = constraints.max_num_ects {
+            self.model.add_constr(
+                "max_ects",
+                self.amount_ects.clone(),
+                ConstrSense::Less,
+                max_ects as f64,
+            );
+            self.push_last_constr_group("max_num_ects", format!("max_num_ects {}", max_ects))
+                .bound = Some((self.amount_ects.clone(), ModelSense::Minimize));
+        }
+        if let Some(target) = &constraints.target_ects {
+            if target.min > target.max {
+                return Err(SchedularError::InvalidConstraint(format!(
+                    "target_ects range {}..{} is empty",
+                    target.min, target.max
+                )));
+            }
+        }
+        self.target_ects = constraints.target_ects.clone();
 
         if let Some(courses_per_faculty) = &constraints.max_courses_per_faculty {
             for (fac, num) in courses_per_faculty.iter() {
                 if let Some(expr) = self.faculties.get(fac) {
-                    self.model.add_constr(fac, c!(expr.clone() <= num))?;
+                    self.model
+                        .add_constr(fac, expr.clone(), ConstrSense::Less, *num as f64);
+                    self.push_last_constr_group(
+                        "max_courses_per_faculty",
+                        format!("max_courses_per_faculty {} {}", fac, num),
+                    );
                 }
             }
         }
         if let Some(max_days) = constraints.max_num_days {
-            let mut weekday_sum_expr = LinExpr::new();
-            for weekday in WEEKDAYS {
-                let weekday_var_name = format!("{}_v", weekday);
-                let weekday_var = self
-                    .model
-                    .add_var(&weekday_var_name, Binary, 0., 0., 1., [])?;
-                if let Some(weekday_expr) = self.weekday_exprs.get(weekday) {
-                    self.model.add_constr(
-                        &format!("{}_is_off", weekday),
-                        c!(weekday_expr.clone() >= weekday_var),
-                    )?;
-                    for (num, on_this_day_var) in self
-                        .on_weekday_vars
-                        .get(weekday)
-                        .expect("should contain elements because of previous call")
+            self.model.add_constr(
+                "weekday_sum_constr",
+                self.amount_weekdays.clone(),
+                ConstrSense::Less,
+                max_days as f64,
+            );
+            self.push_last_constr_group("max_num_days", format!("max_num_days {}", max_days))
+                .bound = Some((self.amount_weekdays.clone(), ModelSense::Minimize));
+        }
+        self.add_free_time_constraints(constraints)?;
+        self.add_selection_locks(
+            constraints.locked_selections.as_deref().unwrap_or_default(),
+            constraints.banned_selections.as_deref().unwrap_or_default(),
+        )?;
+        Ok(())
+    }
+
+    /// Fixes the binaries of locked selections to one and those of banned selections to zero.
+    fn add_selection_locks(
+        &mut self,
+        locked_selections: &[String],
+        banned_selections: &[String],
+    ) -> Result<(), SchedularError> {
+        for id in locked_selections {
+            let selection = self.selection_expr(id)?;
+            self.model
+                .add_constr(&format!("locked_{}", id), selection, ConstrSense::Equal, 1.);
+            self.push_last_constr_group("locked_selections", format!("locked selection {}", id));
+        }
+        for id in banned_selections {
+            let selection = self.selection_expr(id)?;
+            self.model
+                .add_constr(&format!("banned_{}", id), selection, ConstrSense::Equal, 0.);
+            self.push_last_constr_group("banned_selections", format!("banned selection {}", id));
+        }
+        Ok(())
+    }
+
+    /// Sums every selection with the id, selections added twice are locked or banned together.
+    fn selection_expr(&self, id: &str) -> Result<LinExpr, SchedularError> {
+        let Some(vars) = self.selection_vars.get(id) else {
+            return Err(SchedularError::UnknownSelection(id.to_string()));
+        };
+        Ok(LinExpr::sum(vars))
+    }
+
+    /// Bounds the contact minutes of every day, returns the indices of the added constraints.
+    fn add_max_hours_per_day(&mut self, max_hours: f64) -> Result<Range<usize>, SchedularError> {
+        if !(0. ..=24.).contains(&max_hours) {
+            return Err(SchedularError::InvalidConstraint(
+                "max_hours_per_day has to be between 0 and 24".to_string(),
+            ));
+        }
+        let mut minutes_per_day: HashMap<&String, LinExpr> = HashMap::new();
+        for session in self.sessions.iter() {
+            minutes_per_day
+                .entry(&session.weekday)
+                .or_default()
+                .add_term(
+                    (session.to - session.from).num_minutes() as f64,
+                    session.var,
+                );
+        }
+        let minutes_per_day = minutes_per_day
+            .into_iter()
+            .sorted_by_key(|(weekday, _)| *weekday)
+            .map(|(weekday, minutes)| (weekday.clone(), minutes))
+            .collect_vec();
+        let start = self.model.constraints().len();
+        for (weekday, minutes) in minutes_per_day {
+            self.model.add_constr(
+                &format!("{}_max_hours", weekday),
+                minutes,
+                ConstrSense::Less,
+                max_hours * 60.,
+            );
+        }
+        Ok(start..self.model.constraints().len())
+    }
+
+    /// Records the most recently added constraint as the group of a single setting.
+    fn push_last_constr_group(
+        &mut self,
+        setting: &'static str,
+        description: String,
+    ) -> &mut ConstraintGroup {
+        let index = self.model.constraints().len() - 1;
+        self.constraint_groups
+            .push(ConstraintGroup::new(setting, description, index..index + 1));
+        self.constraint_groups
+            .last_mut()
+            .expect("should have been pushed right before")
+    }
+
+    /// Adds slack to the constraints of every soft setting and
+    /// penalizes their violation in the objective.
+    pub fn soften_constraints(&mut self) {
+        for group in self.constraint_groups.iter() {
+            let Some(&penalty) = self.soft_penalties.get(group.setting) else {
+                continue;
+            };
+            let mut amount = LinExpr::new();
+            for index in group.constraints.clone() {
+                amount.add_expr(1., &self.model.relax_constr(index));
+            }
+            self.penalty.add_expr(penalty, &amount);
+            self.soft_violations.push(SoftViolation {
+                setting: group.setting,
+                description: group.description.clone(),
+                amount,
+            });
+        }
+    }
+
+    fn violations(&self, solution: &Solution) -> Vec<Violation> {
+        self.soft_violations
+            .iter()
+            .filter_map(|violation| {
+                let slack = violation.amount.evaluate(solution.values());
+                if slack < 1e-6 {
+                    return None;
+                }
+                let (amount, message) = describe_violation(violation.setting, slack);
+                Some(Violation {
+                    constraint: violation.description.clone(),
+                    amount,
+                    message,
+                })
+            })
+            .collect()
+    }
+
+    pub fn add_required_courses(
+        &mut self,
+        required_courses: &[String],
+    ) -> Result<(), SchedularError> {
+        for subject in required_courses {
+            let Some(variants) = self.subject_variants.get(subject) else {
+                return Err(SchedularError::RequiredCourseUnavailable(subject.clone()));
+            };
+            self.model.add_constr(
+                &format!("required_{}", subject),
+                LinExpr::sum(variants.iter()),
+                ConstrSense::Equal,
+                1.,
+            );
+            self.push_last_constr_group("required_courses", format!("required course {}", subject));
+        }
+        Ok(())
+    }
+
+    fn add_free_time_constraints(
+        &mut self,
+        constraints: &ConstraintSettings,
+    ) -> Result<(), SchedularError> {
+        if let Some(min_free_days) = constraints.min_free_days {
+            if !(0..=WEEKDAYS.len() as i32).contains(&min_free_days) {
+                return Err(SchedularError::InvalidConstraint(format!(
+                    "min_free_days has to be between 0 and {}",
+                    WEEKDAYS.len()
+                )));
+            }
+            let attended_weekdays = LinExpr::sum(
+                WEEKDAYS
+                    .iter()
+                    .filter_map(|weekday| self.weekday_vars.get(*weekday)),
+            );
+            self.model.add_constr(
+                "min_free_days",
+                attended_weekdays,
+                ConstrSense::Less,
+                (WEEKDAYS.len() as i32 - min_free_days) as f64,
+            );
+            self.push_last_constr_group(
+                "min_free_days",
+                format!("min_free_days {}", min_free_days),
+            );
+        }
+
+        self.free_hours = constraints.free_hours.clone();
+        if let Some(free_hours) = &constraints.free_hours {
+            if free_hours.from >= free_hours.to || free_hours.hours < 0. {
+                return Err(SchedularError::InvalidConstraint(
+                    "free_hours needs a non negative amount of hours and a window that starts before it ends"
+                        .to_string(),
+                ));
+            }
+            let window = (free_hours.from, free_hours.to);
+            let available_minutes: i64 = WEEKDAYS
+                .iter()
+                .flat_map(|weekday| {
+                    let blocked = self
+                        .blocked_slots
                         .iter()
-                        .enumerate()
-                    {
-                        self.model.add_constr(
-                            &format!("{}_is_on_{}", weekday, num),
-                            c!(on_this_day_var <= weekday_var),
-                        )?;
-                    }
-                    weekday_sum_expr.add_term(1., weekday_var);
+                        .filter(|slot| slot.weekday == *weekday)
+                        .map(|slot| (slot.from, slot.to))
+                        .collect_vec();
+                    free_intervals(window.0, window.1, blocked)
+                })
+                .map(|(from, to)| (to - from).num_minutes())
+                .sum();
+            let required_minutes = free_hours.hours * 60.;
+            if required_minutes > available_minutes as f64 {
+                return Err(SchedularError::InvalidConstraint(format!(
+                    "only {:.1} hours are available between {} and {}",
+                    available_minutes as f64 / 60.,
+                    free_hours.from,
+                    free_hours.to
+                )));
+            }
+
+            let mut occupied_minutes = LinExpr::new();
+            for (&var, course_selection) in self.selections.iter() {
+                let minutes_in_window: i64 = course_selection
+                    .appointments
+                    .iter()
+                    .filter(|appointment| WEEKDAYS.contains(&appointment.weekday.as_str()))
+                    .map(|appointment| overlap_minutes((appointment.from, appointment.to), window))
+                    .sum();
+                if minutes_in_window > 0 {
+                    occupied_minutes.add_term(minutes_in_window as f64, var);
                 }
             }
-            self.model
-                .add_constr("weekday_sum_constr", c!(weekday_sum_expr <= max_days))?;
+            self.model.add_constr(
+                "free_hours",
+                occupied_minutes,
+                ConstrSense::Less,
+                available_minutes as f64 - required_minutes,
+            );
+            self.push_last_constr_group("free_hours", format!("free_hours {}", free_hours.hours));
         }
         Ok(())
     }
 
-    fn set_objective(&mut self, objective: &SolutionObjective) -> Result<(), SchedularError> {
-        match objective {
-            SolutionObjective::MinimizeNumCourses => self
-                .model
-                .set_objective(self.vars.iter().grb_sum(), Minimize),
-            SolutionObjective::MaximizeNumEcts => {
-                self.model.set_objective(self.amount_ects.clone(), Maximize)
+    /// Models the time between the first and the last appointment of each day on the
+    /// segments between consecutive start and end times. `started` is monotonically
+    /// increasing and at least one from the first appointment on, `unfinished` is
+    /// monotonically decreasing and at least one up to the last appointment, so both are
+    /// one exactly while being on campus.
+    fn add_campus_time(&mut self) -> CampusTimeExprs {
+        let mut campus_time = CampusTimeExprs {
+            span: LinExpr::new(),
+            idle: LinExpr::new(),
+        };
+        for weekday in WEEKDAYS {
+            let appointments = self
+                .selections
+                .iter()
+                .flat_map(|(&var, course)| {
+                    course
+                        .appointments
+                        .iter()
+                        .filter(|appointment| appointment.weekday == weekday)
+                        .map(move |appointment| (appointment.from, appointment.to, var))
+                })
+                .collect_vec();
+            let breakpoints = appointments
+                .iter()
+                .flat_map(|&(from, to, _)| [from, to])
+                .sorted()
+                .dedup()
+                .collect_vec();
+            let num_segments = breakpoints.len().saturating_sub(1);
+            let segment_index = |time: NaiveTime| {
+                breakpoints
+                    .binary_search(&time)
+                    .expect("every start and end is a breakpoint")
+            };
+
+            let mut occupied = vec![LinExpr::new(); num_segments];
+            let mut first_of = vec![vec![]; num_segments];
+            let mut last_of = vec![vec![]; num_segments];
+            for (from, to, var) in appointments {
+                if from >= to {
+                    continue;
+                }
+                let first = segment_index(from);
+                let last = segment_index(to) - 1;
+                first_of[first].push(var);
+                last_of[last].push(var);
+                for occupied_expr in occupied[first..=last].iter_mut() {
+                    occupied_expr.add_term(1., var);
+                }
+            }
+
+            let mut previous: Option<(Var, Var)> = None;
+            for index in 0..num_segments {
+                let name = format!("{}_{}", weekday, breakpoints[index]);
+                let minutes = (breakpoints[index + 1] - breakpoints[index]).num_minutes() as f64;
+                let started =
+                    self.model
+                        .add_var(&format!("{}_started", name), VarType::Continuous, 0., 1.);
+                let unfinished = self.model.add_var(
+                    &format!("{}_unfinished", name),
+                    VarType::Continuous,
+                    0.,
+                    1.,
+                );
+                for (num, &var) in first_of[index].iter().enumerate() {
+                    let mut expr = LinExpr::new();
+                    expr.add_term(1., started);
+                    expr.add_term(-1., var);
+                    self.model.add_constr(
+                        &format!("{}_starts_{}", name, num),
+                        expr,
+                        ConstrSense::Greater,
+                        0.,
+                    );
+                }
+                for (num, &var) in last_of[index].iter().enumerate() {
+                    let mut expr = LinExpr::new();
+                    expr.add_term(1., unfinished);
+                    expr.add_term(-1., var);
+                    self.model.add_constr(
+                        &format!("{}_ends_{}", name, num),
+                        expr,
+                        ConstrSense::Greater,
+                        0.,
+                    );
+                }
+                if let Some((previous_started, previous_unfinished)) = previous {
+                    let mut started_expr = LinExpr::new();
+                    started_expr.add_term(1., started);
+                    started_expr.add_term(-1., previous_started);
+                    self.model.add_constr(
+                        &format!("{}_stays_started", name),
+                        started_expr,
+                        ConstrSense::Greater,
+                        0.,
+                    );
+                    let mut unfinished_expr = LinExpr::new();
+                    unfinished_expr.add_term(1., previous_unfinished);
+                    unfinished_expr.add_term(-1., unfinished);
+                    self.model.add_constr(
+                        &format!("{}_stays_unfinished", name),
+                        unfinished_expr,
+                        ConstrSense::Greater,
+                        0.,
+                    );
+                }
+                previous = Some((started, unfinished));
+
+                let on_campus =
+                    self.model
+                        .add_var(&format!("{}_on_campus", name), VarType::Continuous, 0., 1.);
+                let mut on_campus_expr = LinExpr::new();
+                on_campus_expr.add_term(1., on_campus);
+                on_campus_expr.add_term(-1., started);
+                on_campus_expr.add_term(-1., unfinished);
+                self.model.add_constr(
+                    &format!("{}_is_on_campus", name),
+                    on_campus_expr,
+                    ConstrSense::Greater,
+                    -1.,
+                );
+                let idle =
+                    self.model
+                        .add_var(&format!("{}_idle", name), VarType::Continuous, 0., 1.);
+                let mut idle_expr = LinExpr::new();
+                idle_expr.add_term(1., idle);
+                idle_expr.add_term(-1., on_campus);
+                idle_expr.add_expr(1., &occupied[index]);
+                self.model.add_constr(
+                    &format!("{}_is_idle", name),
+                    idle_expr,
+                    ConstrSense::Greater,
+                    0.,
+                );
+                campus_time.span.add_term(minutes, on_campus);
+                campus_time.idle.add_term(minutes, idle);
+            }
+        }
+        campus_time
+    }
+
+    fn day_summaries(course_selections: &[CourseSelection]) -> Vec<DaySummary> {
+        WEEKDAYS
+            .iter()
+            .filter_map(|weekday| {
+                let busy = course_selections
+                    .iter()
+                    .flat_map(|course| course.appointments.iter())
+                    .filter(|appointment| appointment.weekday == *weekday)
+                    .map(|appointment| (appointment.from, appointment.to))
+                    .collect_vec();
+                let first_start = busy.iter().map(|(from, _)| *from).min()?;
+                let last_end = busy.iter().map(|(_, to)| *to).max()?;
+                let idle_minutes = free_intervals(first_start, last_end, busy)
+                    .into_iter()
+                    .map(|(from, to)| (to - from).num_minutes())
+                    .sum();
+                Some(DaySummary {
+                    weekday: weekday.to_string(),
+                    span_minutes: (last_end - first_start).num_minutes(),
+                    idle_minutes,
+                })
+            })
+            .collect()
+    }
+
+    fn free_days(course_selections: &[CourseSelection]) -> Vec<String> {
+        WEEKDAYS
+            .iter()
+            .filter(|weekday| {
+                !course_selections
+                    .iter()
+                    .any(|course| course.takes_place_on(&weekday.to_string()))
+            })
+            .map(|weekday| weekday.to_string())
+            .collect()
+    }
+
+    fn free_slots(&self, course_selections: &[CourseSelection]) -> Vec<TimeSlot> {
+        let Some(free_hours) = &self.free_hours else {
+            return vec![];
+        };
+        WEEKDAYS
+            .iter()
+            .flat_map(|weekday| {
+                let busy = course_selections
+                    .iter()
+                    .flat_map(|course| course.appointments.iter())
+                    .filter(|appointment| appointment.weekday == *weekday)
+                    .map(|appointment| (appointment.from, appointment.to))
+                    .chain(
+                        self.blocked_slots
+                            .iter()
+                            .filter(|slot| slot.weekday == *weekday)
+                            .map(|slot| (slot.from, slot.to)),
+                    )
+                    .collect_vec();
+                free_intervals(free_hours.from, free_hours.to, busy)
+                    .into_iter()
+                    .map(|(from, to)| TimeSlot {
+                        weekday: weekday.to_string(),
+                        from,
+                        to,
+                    })
+            })
+            .collect()
+    }
+
+    /// Expression for the amount of ECTS outside of the target range, measured by two
+    /// non negative slack variables for falling short of and exceeding the range.
+    fn add_ects_deviation(&mut self, target: &EctsRange) -> LinExpr {
+        let below = self
+            .model
+            .add_var("ects_below_target", VarType::Continuous, 0., f64::INFINITY);
+        let above = self
+            .model
+            .add_var("ects_above_target", VarType::Continuous, 0., f64::INFINITY);
+        let mut lower_expr = self.amount_ects.clone();
+        lower_expr.add_term(1., below);
+        self.model.add_constr(
+            "ects_target_min",
+            lower_expr,
+            ConstrSense::Greater,
+            target.min,
+        );
+        let mut upper_expr = self.amount_ects.clone();
+        upper_expr.add_term(-1., above);
+        self.model
+            .add_constr("ects_target_max", upper_expr, ConstrSense::Less, target.max);
+        LinExpr::sum([below, above].iter())
+    }
+
+    fn campus_time(&mut self) -> CampusTimeExprs {
+        if self.campus_time.is_none() {
+            self.campus_time = Some(self.add_campus_time());
+        }
+        self.campus_time
+            .clone()
+            .expect("should have been added right before")
+    }
+
+    fn objective_expr(
+        &mut self,
+        objective: &SolutionObjective,
+    ) -> Result<(LinExpr, ModelSense), SchedularError> {
+        let objective_expr = match objective {
+            SolutionObjective::MinimizeNumCourses => (
+                LinExpr::sum(self.subject_vars.values()),
+                ModelSense::Minimize,
+            ),
+            SolutionObjective::MaximizeNumEcts => (self.amount_ects.clone(), ModelSense::Maximize),
+            SolutionObjective::MaximizePreference => {
+                (self.amount_preference.clone(), ModelSense::Maximize)
             }
             SolutionObjective::MinimizeNumWeekdays => {
-                todo!()
+                (self.amount_weekdays.clone(), ModelSense::Minimize)
+            }
+            SolutionObjective::MinimizeEctsDeviation => {
+                let Some(target) = self.target_ects.clone() else {
+                    return Err(SchedularError::InvalidConstraint(
+                        "the ectstarget objective needs a target_ects range".to_string(),
+                    ));
+                };
+                if self.ects_deviation.is_none() {
+                    self.ects_deviation = Some(self.add_ects_deviation(&target));
+                }
+                let deviation = self
+                    .ects_deviation
+                    .clone()
+                    .expect("should have been added right before");
+                (deviation, ModelSense::Minimize)
+            }
+            SolutionObjective::MinimizeGaps => (self.campus_time().idle, ModelSense::Minimize),
+            SolutionObjective::MinimizeSpan => (self.campus_time().span, ModelSense::Minimize),
+            SolutionObjective::NoObjective => (LinExpr::new(), ModelSense::Minimize),
+        };
+        Ok(objective_expr)
+    }
+
+    /// Splits the objectives into levels of equal priority, each one a weighted sum to be minimized.
+    /// Penalties of violated soft constraints count towards the highest priority level.
+    fn set_objective(&mut self, objectives: &ObjectiveSettings) -> Result<(), SchedularError> {
+        let mut levels: BTreeMap<Reverse<i32>, LinExpr> = BTreeMap::new();
+        for component in objectives.components() {
+            // a negative weight would maximize idle time, span or ects deviation, which
+            // are only bounded from below
+            if !component.weight.is_finite() || component.weight <= 0. {
+                return Err(SchedularError::InvalidConstraint(format!(
+                    "weight of objective {:?} has to be a positive number",
+                    component.objective
+                )));
+            }
+            let (expr, sense) = self.objective_expr(&component.objective)?;
+            let sign = match sense {
+                ModelSense::Minimize => 1.,
+                ModelSense::Maximize => -1.,
+            };
+            levels
+                .entry(Reverse(component.priority))
+                .or_default()
+                .add_expr(sign * component.weight, &expr);
+            self.objective_components.push((component.objective, expr));
+        }
+        self.objective_levels = levels.into_values().collect();
+        if !self.penalty.is_empty() {
+            match self.objective_levels.first_mut() {
+                Some(level) => level.add_expr(1., &self.penalty),
+                None => self.objective_levels.push(self.penalty.clone()),
             }
-            SolutionObjective::NoObjective => self.model.set_objective(0, Minimize),
-        }?;
+        }
+        let objective = self.objective_levels.last().cloned().unwrap_or_default();
+        self.model.set_objective(objective, ModelSense::Minimize);
         Ok(())
     }
 
+    /// Optimizes the objective levels one after another. Each level's optimum is kept as
+    /// a constraint while optimizing the next one, only the last level returns a solution pool.
+    fn optimize(&mut self, solver: &dyn Solver) -> Result<Vec<Solution>, SchedularError> {
+        let levels = self.objective_levels.clone();
+        let Some((last_level, higher_levels)) = levels.split_last() else {
+            return self.run_solver(solver, &self.model, self.max_num_solutions, 0);
+        };
+        for (num, level) in higher_levels.iter().enumerate() {
+            self.model
+                .set_objective(level.clone(), ModelSense::Minimize);
+            let Some(best) = self
+                .run_solver(solver, &self.model, 1, num)?
+                .into_iter()
+                .next()
+            else {
+                return Ok(vec![]);
+            };
+            if !best.is_optimal() {
+                // out of time, the lower levels cannot build on an unproven optimum
+                return Ok(vec![best]);
+            }
+            let optimum = best.objective_value();
+            let tolerance = LEXICOGRAPHIC_TOLERANCE * optimum.abs().max(1.);
+            self.model.add_constr(
+                &format!("objective_level_{}", num),
+                level.clone(),
+                ConstrSense::Less,
+                optimum + tolerance,
+            );
+        }
+        self.model
+            .set_objective(last_level.clone(), ModelSense::Minimize);
+        self.solution_pool(solver)
+    }
+
+    /// With a minimum subject distance the pool is built one solution at a time, each
+    /// solution cutting off all subject sets too close to it. Otherwise the solver's
+    /// own pool is used, which may only differ in the chosen variants.
+    fn solution_pool(&self, solver: &dyn Solver) -> Result<Vec<Solution>, SchedularError> {
+        let level = self.objective_levels.len().saturating_sub(1);
+        let Some(min_distance) = self.min_subject_distance else {
+            return self.run_solver(solver, &self.model, self.max_num_solutions, level);
+        };
+        let subject_vars = self
+            .subject_vars
+            .iter()
+            .sorted_by_key(|(subject, _)| *subject)
+            .map(|(_, &var)| var)
+            .collect_vec();
+        let mut model = self.model.clone();
+        let mut solutions: Vec<Solution> = vec![];
+        while solutions.len() < self.max_num_solutions {
+            let solution = match self.run_solver(solver, &model, 1, level) {
+                Ok(solutions) => match solutions.into_iter().next() {
+                    Some(solution) => solution,
+                    None => break,
+                },
+                Err(SchedularError::TimeLimitReached) if !solutions.is_empty() => break,
+                Err(err) => return Err(err),
+            };
+            let optimal = solution.is_optimal();
+            model.add_hamming_cut(&subject_vars, &solution, min_distance);
+            solutions.push(solution);
+            if !optimal {
+                break;
+            }
+        }
+        Ok(solutions)
+    }
+
+    /// Solves `model`, forwarding the solver's progress to the observer if there is one.
+    fn run_solver(
+        &self,
+        solver: &dyn Solver,
+        model: &Model,
+        max_num_solutions: usize,
+        level: usize,
+    ) -> Result<Vec<Solution>, SchedularError> {
+        let Some(observer) = &self.progress_observer else {
+            return solver.solve(model, max_num_solutions);
+        };
+        solver.solve_observed(model, max_num_solutions, &|progress| match progress {
+            SolverProgress::Incumbent(solution) => observer(SolveProgress::Incumbent {
+                schedule: Box::new(self.schedule_from(&solution)),
+            }),
+            SolverProgress::Bound { objective, bound } => observer(SolveProgress::Bound {
+                level,
+                objective,
+                bound,
+                gap: relative_gap(objective, bound),
+            }),
+        })
+    }
+
     pub fn solve(
         &mut self,
         filter_settings: FilterSettings,
         constraint_settings: &ConstraintSettings,
-        objective: &SolutionObjective,
+        objective: &ObjectiveSettings,
+        limits: SolveLimits,
     ) -> Result<Vec<SolutionSchedule>, SchedularError> {
-        let conn = &mut connection().expect("should be able to establish connection to db");
-        let possible_lectures = CourseSelection::addmissiable_lectures(conn, filter_settings)
-            .expect("should be able to request possible lectures");
+        self.solve_with(
+            default_solver(limits).as_ref(),
+            filter_settings,
+            constraint_settings,
+            objective,
+        )
+    }
 
-        let course_selections = CourseSelection::build_from_lectures(possible_lectures);
-        self.add_courses(course_selections.clone())?;
-        self.add_constraints(constraint_settings)?;
+    pub fn solve_with(
+        &mut self,
+        solver: &dyn Solver,
+        filter_settings: FilterSettings,
+        constraint_settings: &ConstraintSettings,
+        objective: &ObjectiveSettings,
+    ) -> Result<Vec<SolutionSchedule>, SchedularError> {
+        self.build(filter_settings, constraint_settings)?;
+        self.solve_built(solver, constraint_settings, objective)
+    }
+
+    /// Optimizes the built model, explaining why if it is infeasible.
+    fn solve_built(
+        &mut self,
+        solver: &dyn Solver,
+        constraint_settings: &ConstraintSettings,
+        objective: &ObjectiveSettings,
+    ) -> Result<Vec<SolutionSchedule>, SchedularError> {
+        let feasibility_model = self.model.clone();
         self.set_objective(objective)?;
-        self.model.set_param(PoolSearchMode, 2)?;
-        self.model.update()?;
-        println!("Writing model to file");
-        self.model.write("schedular.lp")?;
-        self.model.optimize()?;
-
-        let solution_count = self.model.get_attr(SolCount)?;
-
-        let courses_iterator = course_selections.iter();
-        (0..solution_count)
-            .map(|index| {
-                self.model.set_param(SolutionNumber, index)?;
-                let objective_value = self.model.get_attr(ObjVal)?;
-                let solution_vec = self.model.get_obj_attr_batch(attr::Xn, self.vars.clone())?;
-                let course_selections = courses_iterator
-                    .clone()
-                    .zip(solution_vec.iter())
-                    .filter_map(|(course, &val)| {
-                        if val == 1. {
-                            Some(course.clone())
-                        } else {
-                            None
+
+        let solutions = self.optimize(solver)?;
+        if solutions.is_empty() {
+            let conflict =
+                explain_infeasibility(&feasibility_model, &self.constraint_groups, solver)?;
+            return Err(SchedularError::Infeasible(conflict));
+        }
+
+        let schedules = solutions
+            .iter()
+            .map(|solution| self.schedule_from(solution))
+            .collect();
+        Self::verified(schedules, constraint_settings)
+    }
+
+    /// Re-checks the schedules without the model. Schedules that break a hard constraint
+    /// are logged and dropped, it is an error if none is left.
+    fn verified(
+        schedules: Vec<SolutionSchedule>,
+        constraint_settings: &ConstraintSettings,
+    ) -> Result<Vec<SolutionSchedule>, SchedularError> {
+        let mut valid = vec![];
+        let mut breaches = vec![];
+        for schedule in schedules {
+            let found = verify(&schedule.course_selections, constraint_settings);
+            if found.is_empty() {
+                valid.push(schedule);
+            } else {
+                tracing::error!("Dropping a schedule that breaks constraints: {:?}", found);
+                breaches.extend(found);
+            }
+        }
+        if valid.is_empty() && !breaches.is_empty() {
+            let messages = breaches.iter().map(|breach| &breach.message).join("; ");
+            return Err(SchedularError::UnverifiedSchedule(messages));
+        }
+        Ok(valid)
+    }
+
+    /// Solves the request once with the quarter hour overlap constraints and once with
+    /// the exact ones, everything else about the model stays the same. Both solves get
+    /// the full time limit.
+    pub fn benchmark(
+        &mut self,
+        solver_settings: &SolverSettings,
+        limits: SolveLimits,
+        filter_settings: FilterSettings,
+        constraint_settings: &ConstraintSettings,
+        objective: &ObjectiveSettings,
+    ) -> Result<ModelBenchmark, SchedularError> {
+        self.build(filter_settings, constraint_settings)?;
+        self.set_objective(objective)?;
+        let clique_model = self.model.clone();
+        let quarter_hour_model = self.quarter_hour_model();
+        let num_clique_constraints = clique_model.constraints().len();
+        let num_other_constraints = num_clique_constraints
+            - clique_model
+                .constraints()
+                .iter()
+                .filter(|constr| constr.name.starts_with("conflict_"))
+                .count();
+
+        let mut measure = |model: Model| -> Result<ModelMeasurement, SchedularError> {
+            let num_vars = model.vars().len();
+            let num_constraints = model.constraints().len();
+            self.model = model;
+            let solver = solver_settings.solver(limits.clone().start_now());
+            let start = Instant::now();
+            let solutions = self.optimize(solver.as_ref())?;
+            Ok(ModelMeasurement {
+                num_vars,
+                num_constraints,
+                num_overlap_constraints: num_constraints - num_other_constraints,
+                solve_time: start.elapsed(),
+                objective_value: solutions.first().map(Solution::objective_value),
+            })
+        };
+        let quarter_hours = measure(quarter_hour_model)?;
+        let cliques = measure(clique_model)?;
+        Ok(ModelBenchmark {
+            quarter_hours,
+            cliques,
+        })
+    }
+
+    pub fn pareto_front(
+        &mut self,
+        filter_settings: FilterSettings,
+        constraint_settings: &ConstraintSettings,
+        pareto_settings: &ParetoSettings,
+        limits: SolveLimits,
+    ) -> Result<Vec<SolutionSchedule>, SchedularError> {
+        self.pareto_front_with(
+            default_solver(limits).as_ref(),
+            filter_settings,
+            constraint_settings,
+            pareto_settings,
+        )
+    }
+
+    /// Trades the number of weekdays, and optionally the contact hours per day, off against
+    /// the ECTS credits. The model is built once, every point of the sweep only adds its bounds
+    /// to a copy of it. Returns the non-dominated schedules, fewest days first.
+    pub fn pareto_front_with(
+        &mut self,
+        solver: &dyn Solver,
+        filter_settings: FilterSettings,
+        constraint_settings: &ConstraintSettings,
+        pareto_settings: &ParetoSettings,
+    ) -> Result<Vec<SolutionSchedule>, SchedularError> {
+        self.build(filter_settings, constraint_settings)?;
+        self.sweep_pareto_front(solver, constraint_settings, pareto_settings)
+    }
+
+    fn sweep_pareto_front(
+        &mut self,
+        solver: &dyn Solver,
+        constraint_settings: &ConstraintSettings,
+        pareto_settings: &ParetoSettings,
+    ) -> Result<Vec<SolutionSchedule>, SchedularError> {
+        let feasibility_model = self.model.clone();
+        self.set_objective(&ObjectiveSettings::Combined(vec![
+            WeightedObjective {
+                objective: SolutionObjective::MaximizeNumEcts,
+                weight: 1.,
+                priority: 1,
+            },
+            WeightedObjective {
+                objective: SolutionObjective::MinimizeNumWeekdays,
+                weight: 1.,
+                priority: 0,
+            },
+        ]))?;
+        self.max_num_solutions = 1;
+
+        // a soft day limit may be exceeded, so it does not cut the sweep short
+        let max_days = constraint_settings
+            .max_num_days
+            .filter(|_| {
+                !constraint_settings
+                    .soft_constraints
+                    .as_ref()
+                    .is_some_and(|soft| soft.contains_key("max_num_days"))
+            })
+            .map_or(WEEKDAYS.len(), |days| days.max(0) as usize)
+            .min(WEEKDAYS.len());
+        let hour_bounds = match &pareto_settings.max_hours_per_day {
+            Some(hours) if !hours.is_empty() => hours.iter().map(|&hours| Some(hours)).collect(),
+            _ => vec![None],
+        };
+
+        let model = self.model.clone();
+        let mut schedules = vec![];
+        'sweep: for days in 1..=max_days {
+            for max_hours in hour_bounds.iter() {
+                self.model = model.clone();
+                self.model.add_constr(
+                    &format!("pareto_max_days_{}", days),
+                    self.amount_weekdays.clone(),
+                    ConstrSense::Less,
+                    days as f64,
+                );
+                if let Some(max_hours) = max_hours {
+                    self.add_max_hours_per_day(*max_hours)?;
+                }
+                match self.optimize(solver) {
+                    Ok(solutions) => {
+                        if let Some(solution) = solutions.first() {
+                            schedules.push(self.schedule_from(solution));
                         }
-                    })
-                    .collect_vec();
-                let total_ects = course_selections.iter().fold(0., |acc, val| acc + val.ects);
-                let schedule = SolutionSchedule {
-                    objective_value,
-                    total_ects,
-                    course_selections,
-                };
-                Ok(schedule)
+                    }
+                    // return the part of the front swept before running out of time
+                    Err(SchedularError::TimeLimitReached) if !schedules.is_empty() => break 'sweep,
+                    Err(err) => return Err(err),
+                }
+            }
+        }
+        self.model = model;
+
+        if schedules.is_empty() {
+            let conflict =
+                explain_infeasibility(&feasibility_model, &self.constraint_groups, solver)?;
+            return Err(SchedularError::Infeasible(conflict));
+        }
+        let schedules = Self::verified(schedules, constraint_settings)?;
+        let with_hours = hour_bounds.iter().any(Option::is_some);
+        Ok(non_dominated(schedules, with_hours))
+    }
+
+    /// Loads the admissible courses and adds them together with all constraints to the model.
+    fn build(
+        &mut self,
+        filter_settings: FilterSettings,
+        constraint_settings: &ConstraintSettings,
+    ) -> Result<(), SchedularError> {
+        let required_courses = filter_settings
+            .required_courses
+            .cloned()
+            .unwrap_or_default();
+        let preferences = filter_settings.preferences;
+        let possible_lectures = self.course_source.lectures(filter_settings)?;
+
+        let mut course_selections = CourseSelection::build_from_lectures(possible_lectures);
+        if let Some(preferences) = preferences {
+            CourseSelection::apply_preferences(&mut course_selections, preferences);
+        }
+        self.build_from(course_selections, &required_courses, constraint_settings)
+    }
+
+    /// Builds the model without objective from already loaded course selections.
+    fn build_from(
+        &mut self,
+        course_selections: Vec<CourseSelection>,
+        required_courses: &[String],
+        constraint_settings: &ConstraintSettings,
+    ) -> Result<(), SchedularError> {
+        self.add_courses(course_selections);
+        self.add_subject_indicators();
+        self.add_weekday_indicators();
+        self.add_constraints(constraint_settings)?;
+        self.add_required_courses(required_courses)?;
+        self.soften_constraints();
+        Ok(())
+    }
+
+    fn schedule_from(&self, solution: &Solution) -> SolutionSchedule {
+        let course_selections = self
+            .selections
+            .iter()
+            .filter(|(&var, _)| solution.is_selected(var))
+            .map(|(_, course)| course.clone())
+            .collect_vec();
+        let total_ects = course_selections.iter().fold(0., |acc, val| acc + val.ects);
+        let subjects = course_selections
+            .iter()
+            .map(|course| course.subject.clone())
+            .unique()
+            .sorted()
+            .collect_vec();
+        let num_courses = subjects.len();
+        let num_weekdays = course_selections
+            .iter()
+            .flat_map(|course| course.weekdays())
+            .unique()
+            .count();
+        let free_days = Self::free_days(&course_selections);
+        let free_slots = self.free_slots(&course_selections);
+        let days = Self::day_summaries(&course_selections);
+        let objective_values = self
+            .objective_components
+            .iter()
+            .map(|(objective, expr)| ObjectiveValue {
+                objective: *objective,
+                value: expr.evaluate(solution.values()),
             })
-            .collect()
+            .collect();
+        let status = if solution.is_optimal() {
+            SolutionStatus::Optimal
+        } else {
+            SolutionStatus::BestFound
+        };
+        SolutionSchedule {
+            status,
+            gap: solution.gap(),
+            objective_values,
+            total_ects,
+            num_courses,
+            num_weekdays,
+            subjects,
+            blocked_slots: self.blocked_slots.clone(),
+            course_selections,
+            free_days,
+            free_slots,
+            days,
+            violations: self.violations(solution),
+        }
     }
 }
 
@@ -277,6 +1754,8 @@ pub fn test_run() -> Result<(), SchedularError> {
     let filters = FilterSettings {
         semester: Some("24S"),
         excluded_courses: None,
+        required_courses: None,
+        preferences: None,
         courses: Some(&courses),
         faculties: None,
         curriculum: Some("5244"),
@@ -287,16 +1766,1076 @@ pub fn test_run() -> Result<(), SchedularError> {
         max_num_days: None,
         max_num_solutions: Some(2),
         max_courses_per_faculty: None,
+        ..Default::default()
     };
 
     let solutions = scheduling_problem.solve(
         filters,
         &constraints,
-        &SolutionObjective::MinimizeNumCourses,
+        &SolutionObjective::MinimizeNumCourses.into(),
+        SolveLimits::default(),
     )?;
     println!("Result: {:#?}", solutions);
     Ok(())
 }
 
 #[cfg(test)]
-mod test {}
+mod test {
+    use std::{
+        cell::Cell,
+        path::Path,
+        str::FromStr,
+        sync::{Arc, Mutex},
+    };
+
+    use chrono::NaiveTime;
+
+    use crate::schedular::{
+        course_selection::CourseSelection,
+        course_source::InMemoryCourseSource,
+        settings::{
+            ConstraintSettings, DayWindow, EctsRange, FilterSettings, FreeHours, ObjectiveSettings,
+            SolutionObjective, TimeSlot, WeightedObjective,
+        },
+        SchedularError,
+    };
+    use crate::scraper::lecture::Lecture;
+
+    use crate::schedular::solver::{
+        lns::LnsSolver, native::NativeSolver, Model, Solution, SolveLimits, Solver,
+    };
+
+    use itertools::Itertools;
+
+    use super::{
+        describe_violation, free_intervals, non_dominated, overlap_cliques, SchedulingProblem,
+        SolutionSchedule, SolutionStatus, SolveProgress,
+    };
+    use std::collections::HashMap;
+
+    /// Six subjects of the 24S master curriculum 5244 with 36 reachable ECTS, IN2010 and
+    /// IN2064 overlap on Thursday. Also a seminar and a lecture of another semester.
+    const FIXTURE: &str = "resources/fixtures/lectures.json";
+
+    /// Solves a request for all courses of the fixture's curriculum.
+    fn solve_fixture_with(
+        required_courses: &[&str],
+        preferences: &[(&str, f64)],
+        constraints: &ConstraintSettings,
+        objective: &str,
+    ) -> Result<Vec<SolutionSchedule>, SchedularError> {
+        let source = InMemoryCourseSource::from_json_file(Path::new(FIXTURE))
+            .expect("should read the fixture");
+        let required_courses = required_courses
+            .iter()
+            .map(|course| course.to_string())
+            .collect_vec();
+        let preferences = preferences
+            .iter()
+            .map(|(course, preference)| (course.to_string(), *preference))
+            .collect::<HashMap<_, _>>();
+        let objective: ObjectiveSettings =
+            serde_json::from_str(objective).expect("should parse the objective");
+        let filter_settings = FilterSettings {
+            semester: Some("24S"),
+            courses: None,
+            excluded_courses: None,
+            required_courses: Some(&required_courses),
+            preferences: Some(&preferences),
+            faculties: None,
+            curriculum: Some("5244"),
+        };
+        SchedulingProblem::with_course_source(source).solve_with(
+            &NativeSolver::default(),
+            filter_settings,
+            constraints,
+            &objective,
+        )
+    }
+
+    fn solve_fixture(constraints: &ConstraintSettings, objective: &str) -> SolutionSchedule {
+        solve_fixture_with(&[], &[], constraints, objective)
+            .expect("should find a schedule")
+            .remove(0)
+    }
+
+    fn time(text: &str) -> NaiveTime {
+        NaiveTime::from_str(text).unwrap()
+    }
+
+    fn constraints_with_blocked_slot(weekday: &str, from: &str, to: &str) -> ConstraintSettings {
+        ConstraintSettings {
+            blocked_slots: Some(vec![TimeSlot {
+                weekday: weekday.to_string(),
+                from: NaiveTime::from_str(from).unwrap(),
+                to: NaiveTime::from_str(to).unwrap(),
+            }]),
+            ..Default::default()
+        }
+    }
+
+    /// Two overlapping courses on Monday, one on Tuesday and one with variants on Wednesday
+    /// and Thursday, 17 ECTS in total.
+    fn week_lectures() -> Vec<Lecture> {
+        vec![
+            Lecture::new("9:30", "11:30", "Monday", "VO", "JO1111", "First", 4.),
+            Lecture::new("10:00", "12:00", "Monday", "VO", "NE9999", "Second", 6.),
+            Lecture::new("12:30", "14:30", "Tuesday", "VO", "MA0001", "Third", 5.),
+            Lecture::new("8:00", "10:00", "Wednesday", "UE", "MA0002", "Fourth", 3.),
+            Lecture::new("8:00", "10:00", "Thursday", "UE", "MA0002", "Fourth", 3.),
+        ]
+    }
+
+    fn solve_lectures(
+        lectures: Vec<Lecture>,
+        constraints: &ConstraintSettings,
+        objective: &str,
+    ) -> Result<Vec<SolutionSchedule>, SchedularError> {
+        let objective: ObjectiveSettings =
+            serde_json::from_str(objective).expect("should parse the objective");
+        let mut scheduling_problem = SchedulingProblem::new();
+        scheduling_problem.build_from(
+            CourseSelection::build_from_lectures(lectures),
+            &[],
+            constraints,
+        )?;
+        scheduling_problem.solve_built(&NativeSolver::default(), constraints, &objective)
+    }
+
+    #[test]
+    fn test_top_schedules() {
+        let constraints = ConstraintSettings {
+            max_num_days: Some(2),
+            max_num_solutions: Some(3),
+            ..Default::default()
+        };
+        let schedules = solve_lectures(week_lectures(), &constraints, r#""maxects""#)
+            .expect("should find schedules");
+        assert_eq!(schedules.len(), 3);
+        assert_eq!(schedules[0].subjects, vec!["MA0001", "NE9999"]);
+        assert_eq!(schedules[0].total_ects, 11.);
+        assert!(schedules.iter().all(|schedule| schedule.total_ects == 9.
+            && schedule.num_weekdays == 2
+            || schedule.total_ects == 11.));
+        let selections = schedules
+            .iter()
+            .map(|schedule| {
+                schedule
+                    .course_selections
+                    .iter()
+                    .map(|course| (course.subject.clone(), course.variant))
+                    .sorted()
+                    .collect_vec()
+            })
+            .unique()
+            .count();
+        assert_eq!(selections, 3);
+    }
+
+    #[test]
+    fn test_objectives_with_native_solver() {
+        let constraints = ConstraintSettings {
+            min_num_ects: Some(9),
+            ..Default::default()
+        };
+        let solve = |objective: &str| {
+            solve_lectures(week_lectures(), &constraints, objective)
+                .expect("should find a schedule")
+                .remove(0)
+        };
+
+        assert_eq!(solve(r#""maxects""#).total_ects, 14.);
+        assert_eq!(solve(r#""mincourses""#).num_courses, 2);
+        assert_eq!(solve(r#""minweekdays""#).num_weekdays, 2);
+        let schedule = solve(r#""mingaps""#);
+        assert!(schedule.days.iter().all(|day| day.idle_minutes == 0));
+        let schedule = solve(r#""minspan""#);
+        let span: i64 = schedule.days.iter().map(|day| day.span_minutes).sum();
+        assert_eq!(span, 240);
+
+        let constraints = ConstraintSettings {
+            min_num_ects: Some(20),
+            ..Default::default()
+        };
+        match solve_lectures(week_lectures(), &constraints, r#""maxects""#) {
+            Err(SchedularError::Infeasible(conflict)) => {
+                assert_eq!(conflict.constraints, vec!["min_num_ects 20"]);
+                assert_eq!(conflict.achievable[0].value, 14.);
+            }
+            other => panic!("expected an infeasible request, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_heuristic_solver() {
+        let mut scheduling_problem = SchedulingProblem::new();
+        scheduling_problem
+            .build_from(
+                CourseSelection::build_from_lectures(week_lectures()),
+                &[],
+                &ConstraintSettings::default(),
+            )
+            .expect("should build the model");
+        // every subject is one group of its variants and its indicator
+        let group_sizes = scheduling_problem
+            .model
+            .decision_groups()
+            .iter()
+            .map(|group| group.len())
+            .collect_vec();
+        assert_eq!(group_sizes, vec![2, 2, 3, 2]);
+
+        let objective: ObjectiveSettings = serde_json::from_str(r#""maxects""#).unwrap();
+        let limits = SolveLimits::new(Some(std::time::Duration::from_secs(5)), None);
+        let schedules = scheduling_problem
+            .solve_built(
+                &LnsSolver::new(limits),
+                &ConstraintSettings::default(),
+                &objective,
+            )
+            .expect("should find a schedule");
+        assert_eq!(schedules[0].total_ects, 14.);
+        assert!(schedules[0].gap.is_some_and(|gap| gap >= 0.));
+    }
+
+    #[test]
+    fn test_fixture_objectives() {
+        let schedule = solve_fixture(&ConstraintSettings::default(), r#""maxects""#);
+        assert_eq!(schedule.total_ects, 36.);
+        assert_eq!(schedule.num_courses, 5);
+        assert!(!schedule.subjects.contains(&"MA5120".to_string()));
+
+        let min_ects = |ects| ConstraintSettings {
+            min_num_ects: Some(ects),
+            ..Default::default()
+        };
+        assert_eq!(
+            solve_fixture(&min_ects(18), r#""mincourses""#).num_courses,
+            2
+        );
+        assert_eq!(
+            solve_fixture(&min_ects(10), r#""minweekdays""#).num_weekdays,
+            2
+        );
+        let schedule = solve_fixture(&min_ects(10), r#""mingaps""#);
+        assert!(schedule.total_ects >= 10.);
+        assert!(schedule.days.iter().all(|day| day.idle_minutes == 0));
+        let schedule = solve_fixture(&min_ects(10), r#""minspan""#);
+        let span: i64 = schedule.days.iter().map(|day| day.span_minutes).sum();
+        assert_eq!(span, 300);
+        assert!(solve_fixture(&min_ects(30), r#""noobjective""#).total_ects >= 30.);
+
+        let constraints = ConstraintSettings {
+            target_ects: Some(EctsRange { min: 14., max: 14. }),
+            ..Default::default()
+        };
+        assert_eq!(
+            solve_fixture(&constraints, r#""ectstarget""#).total_ects,
+            14.
+        );
+
+        let schedule = solve_fixture_with(
+            &[],
+            &[("IN2064", 3.)],
+            &ConstraintSettings::default(),
+            r#""maxpreference""#,
+        )
+        .expect("should find a schedule")
+        .remove(0);
+        assert!(schedule.subjects.contains(&"IN2064".to_string()));
+        assert!(!schedule.subjects.contains(&"IN2010".to_string()));
+    }
+
+    #[test]
+    fn test_fixture_constraints() {
+        let max_ects = |constraints: ConstraintSettings| {
+            solve_fixture(&constraints, r#""maxects""#).total_ects
+        };
+        let time_slot = |weekday: &str, from: &str, to: &str| TimeSlot {
+            weekday: weekday.to_string(),
+            from: time(from),
+            to: time(to),
+        };
+
+        let constraints = ConstraintSettings {
+            max_num_ects: Some(20),
+            ..Default::default()
+        };
+        assert_eq!(max_ects(constraints), 19.);
+        let constraints = ConstraintSettings {
+            max_num_days: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(max_ects(constraints), 10.);
+        let constraints = ConstraintSettings {
+            min_free_days: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(max_ects(constraints), 10.);
+        let constraints = ConstraintSettings {
+            max_courses_per_faculty: Some(vec![("IN".to_string(), 0)]),
+            ..Default::default()
+        };
+        assert_eq!(max_ects(constraints), 28.);
+        let constraints = ConstraintSettings {
+            blocked_slots: Some(vec![time_slot("Monday", "09:00", "13:00")]),
+            ..Default::default()
+        };
+        assert_eq!(max_ects(constraints), 27.);
+        let constraints = ConstraintSettings {
+            day_windows: Some(vec![DayWindow {
+                weekday: None,
+                earliest_start: None,
+                latest_end: Some(time("14:00")),
+            }]),
+            ..Default::default()
+        };
+        assert_eq!(max_ects(constraints), 22.);
+        let constraints = ConstraintSettings {
+            free_hours: Some(FreeHours {
+                hours: 8.,
+                from: time("08:00"),
+                to: time("12:00"),
+            }),
+            ..Default::default()
+        };
+        assert_eq!(max_ects(constraints), 31.);
+        let constraints = ConstraintSettings {
+            max_hours_per_day: Some(3.),
+            ..Default::default()
+        };
+        assert_eq!(max_ects(constraints), 19.);
+
+        let locked = "IN2010/950041/950043@Friday-12:00".to_string();
+        let banned = "MA2003/950001/950002@Tuesday-14:00".to_string();
+        let constraints = ConstraintSettings {
+            locked_selections: Some(vec![locked.clone()]),
+            banned_selections: Some(vec![banned.clone()]),
+            ..Default::default()
+        };
+        let schedule = solve_fixture(&constraints, r#""maxects""#);
+        assert_eq!(schedule.total_ects, 36.);
+        let ids = schedule
+            .course_selections
+            .iter()
+            .map(|course| &course.id)
+            .collect_vec();
+        assert!(ids.contains(&&locked));
+        assert!(!ids.contains(&&banned));
+    }
+
+    #[test]
+    fn test_fixture_pools_and_conflicts() {
+        let constraints = ConstraintSettings {
+            max_num_solutions: Some(3),
+            min_subject_distance: Some(1),
+            ..Default::default()
+        };
+        let schedules = solve_fixture_with(&[], &[], &constraints, r#""maxects""#)
+            .expect("should find schedules");
+        let ects = schedules
+            .iter()
+            .map(|schedule| schedule.total_ects)
+            .collect_vec();
+        assert_eq!(ects, vec![36., 36., 31.]);
+        assert_eq!(
+            schedules
+                .iter()
+                .map(|schedule| &schedule.subjects)
+                .unique()
+                .count(),
+            3
+        );
+
+        let schedule = solve_fixture_with(
+            &["IN2064"],
+            &[],
+            &ConstraintSettings::default(),
+            r#""maxects""#,
+        )
+        .expect("should find a schedule")
+        .remove(0);
+        assert!(schedule.subjects.contains(&"IN2064".to_string()));
+        assert!(matches!(
+            solve_fixture_with(&["MA9999"], &[], &ConstraintSettings::default(), r#""maxects""#),
+            Err(SchedularError::RequiredCourseUnavailable(course)) if course == "MA9999"
+        ));
+
+        let constraints = ConstraintSettings {
+            min_num_ects: Some(40),
+            soft_constraints: Some(HashMap::from([("min_num_ects".to_string(), 1.)])),
+            ..Default::default()
+        };
+        let schedule = solve_fixture(&constraints, r#""maxects""#);
+        assert_eq!(schedule.total_ects, 36.);
+        assert_eq!(schedule.violations.len(), 1);
+        assert_eq!(schedule.violations[0].amount, 4.);
+
+        let constraints = ConstraintSettings {
+            min_num_ects: Some(40),
+            ..Default::default()
+        };
+        match solve_fixture_with(&[], &[], &constraints, r#""maxects""#) {
+            Err(SchedularError::Infeasible(conflict)) => {
+                assert_eq!(conflict.constraints, vec!["min_num_ects 40"]);
+                assert_eq!(conflict.achievable[0].value, 36.);
+            }
+            other => panic!("expected an infeasible request, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_blocked_slot_excludes_overlapping_course() {
+        let lectures = vec![
+            Lecture::new("9:30", "11:30", "Monday", "VO", "JO1111", "First", 4.),
+            Lecture::new("12:30", "14:30", "Tuesday", "VO", "NE9999", "Second", 4.),
+        ];
+        let mut scheduling_problem = SchedulingProblem::new();
+        scheduling_problem.add_courses(CourseSelection::build_from_lectures(lectures));
+        scheduling_problem
+            .add_constraints(&constraints_with_blocked_slot("Monday", "11:00", "12:00"))
+            .expect("should be able to add constraints");
+
+        assert!(!scheduling_problem.model.is_feasible(&[1., 0.]));
+        assert!(scheduling_problem.model.is_feasible(&[0., 1.]));
+    }
+
+    #[test]
+    fn test_overlap_cliques() {
+        let intervals = vec![
+            (time("9:10"), time("10:05")),
+            (time("10:05"), time("11:00")),
+            (time("9:00"), time("9:50")),
+            (time("9:40"), time("10:20")),
+            (time("13:00"), time("14:00")),
+        ];
+        assert_eq!(
+            overlap_cliques(&intervals),
+            vec![vec![0, 2, 3], vec![1, 3], vec![4]]
+        );
+    }
+
+    #[test]
+    fn test_overlap_off_the_quarter_hour() {
+        // the sessions share no quarter hour counted from their starts
+        let lectures = vec![
+            Lecture::new("9:10", "10:00", "Monday", "VO", "JO1111", "First", 4.),
+            Lecture::new("9:00", "9:50", "Monday", "VO", "NE9999", "Second", 4.),
+            Lecture::new("9:50", "11:00", "Monday", "VO", "MA1234", "Third", 4.),
+        ];
+        let mut scheduling_problem = SchedulingProblem::new();
+        scheduling_problem.add_courses(CourseSelection::build_from_lectures(lectures));
+        scheduling_problem
+            .add_constraints(&ConstraintSettings::default())
+            .expect("should be able to add constraints");
+
+        assert!(!scheduling_problem.model.is_feasible(&[1., 1., 0.]));
+        assert!(scheduling_problem.model.is_feasible(&[0., 1., 1.]));
+    }
+
+    #[test]
+    fn test_free_intervals() {
+        let busy = vec![
+            (time("12:00"), time("13:00")),
+            (time("7:00"), time("9:00")),
+            (time("12:30"), time("14:00")),
+        ];
+        let free = free_intervals(time("8:00"), time("18:00"), busy);
+        assert_eq!(
+            free,
+            vec![
+                (time("9:00"), time("12:00")),
+                (time("14:00"), time("18:00"))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_flexible_commitments() {
+        let lectures = vec![
+            Lecture::new("8:00", "12:00", "Monday", "VO", "JO1111", "First", 4.),
+            Lecture::new("8:00", "12:00", "Tuesday", "VO", "NE9999", "Second", 4.),
+            Lecture::new("8:00", "12:00", "Wednesday", "VO", "MA0000", "Third", 4.),
+        ];
+        let mut scheduling_problem = SchedulingProblem::new();
+        scheduling_problem.add_courses(CourseSelection::build_from_lectures(lectures));
+        scheduling_problem.add_weekday_indicators();
+        let constraints = ConstraintSettings {
+            min_free_days: Some(3),
+            free_hours: Some(FreeHours {
+                hours: 40.,
+                from: time("8:00"),
+                to: time("18:00"),
+            }),
+            ..Default::default()
+        };
+        scheduling_problem
+            .add_constraints(&constraints)
+            .expect("should be able to add constraints");
+
+        // course variables first, then one indicator per weekday in order
+        assert!(scheduling_problem
+            .model
+            .is_feasible(&[1., 1., 0., 1., 1., 0.]));
+        assert!(!scheduling_problem
+            .model
+            .is_feasible(&[1., 1., 1., 1., 1., 1.]));
+        assert!(!scheduling_problem
+            .model
+            .is_feasible(&[1., 1., 1., 1., 1., 0.]));
+    }
+
+    #[test]
+    fn test_required_courses() {
+        let lectures = vec![
+            Lecture::new("9:30", "11:30", "Monday", "VO", "JO1111", "First", 4.),
+            Lecture::new("12:30", "14:30", "Monday", "UE", "JO1111", "First", 4.),
+            Lecture::new("12:30", "14:30", "Friday", "UE", "JO1111", "First", 4.),
+            Lecture::new("12:30", "14:30", "Tuesday", "VO", "NE9999", "Second", 4.),
+        ];
+        let mut scheduling_problem = SchedulingProblem::new();
+        scheduling_problem.add_courses(CourseSelection::build_from_lectures(lectures));
+        scheduling_problem
+            .add_required_courses(&["JO1111".to_string()])
+            .expect("should be able to require an offered course");
+
+        assert!(scheduling_problem.model.is_feasible(&[1., 0., 0.]));
+        assert!(!scheduling_problem.model.is_feasible(&[0., 0., 1.]));
+        assert!(!scheduling_problem.model.is_feasible(&[1., 1., 0.]));
+        assert!(scheduling_problem
+            .add_required_courses(&["MA0000".to_string()])
+            .is_err());
+    }
+
+    #[test]
+    fn test_at_most_one_variant_per_subject() {
+        let lectures = vec![
+            Lecture::new("12:30", "14:30", "Monday", "UE", "JO1111", "First", 4.),
+            Lecture::new("12:30", "14:30", "Friday", "UE", "JO1111", "First", 4.),
+        ];
+        let mut scheduling_problem = SchedulingProblem::new();
+        scheduling_problem.add_courses(CourseSelection::build_from_lectures(lectures));
+        scheduling_problem.add_subject_indicators();
+
+        // both variants, then the subject indicator
+        assert!(scheduling_problem.model.is_feasible(&[0., 1., 1.]));
+        assert!(scheduling_problem.model.is_feasible(&[0., 0., 0.]));
+        assert!(!scheduling_problem.model.is_feasible(&[1., 1., 1.]));
+        assert!(!scheduling_problem.model.is_feasible(&[1., 0., 0.]));
+    }
+
+    #[test]
+    fn test_ects_bounds_and_target() {
+        let lectures = vec![
+            Lecture::new("9:30", "11:30", "Monday", "VO", "JO1111", "First", 4.),
+            Lecture::new("12:30", "14:30", "Tuesday", "VO", "NE9999", "Second", 6.),
+        ];
+        let mut scheduling_problem = SchedulingProblem::new();
+        scheduling_problem.add_courses(CourseSelection::build_from_lectures(lectures));
+        let constraints = ConstraintSettings {
+            max_num_ects: Some(8),
+            target_ects: Some(EctsRange { min: 5., max: 7. }),
+            ..Default::default()
+        };
+        scheduling_problem
+            .add_constraints(&constraints)
+            .expect("should be able to add constraints");
+        scheduling_problem
+            .set_objective(&SolutionObjective::MinimizeEctsDeviation.into())
+            .expect("should be able to set the target objective");
+
+        // course variables, then the slack below and above the target
+        assert!(!scheduling_problem.model.is_feasible(&[1., 1., 0., 3.]));
+        assert!(scheduling_problem.model.is_feasible(&[1., 0., 1., 0.]));
+        assert!(scheduling_problem.model.is_feasible(&[0., 1., 0., 0.]));
+        assert!(!scheduling_problem.model.is_feasible(&[0., 0., 0., 0.]));
+    }
+
+    #[test]
+    fn test_objective_levels() {
+        let objectives: ObjectiveSettings = serde_json::from_str(
+            r#"[
+                {"objective": "maxects", "weight": 2},
+                {"objective": "mincourses"},
+                {"objective": "minweekdays", "priority": 1}
+            ]"#,
+        )
+        .expect("should parse combined objectives");
+        let lectures = vec![
+            Lecture::new("9:30", "11:30", "Monday", "VO", "JO1111", "First", 4.),
+            Lecture::new("12:30", "14:30", "Tuesday", "VO", "NE9999", "Second", 6.),
+        ];
+        let mut scheduling_problem = SchedulingProblem::new();
+        scheduling_problem.add_courses(CourseSelection::build_from_lectures(lectures));
+        scheduling_problem.add_subject_indicators();
+        scheduling_problem.add_weekday_indicators();
+        scheduling_problem
+            .set_objective(&objectives)
+            .expect("should be able to set combined objectives");
+
+        // the higher priority level comes first, maximized ects are negated
+        let levels = &scheduling_problem.objective_levels;
+        assert_eq!(levels.len(), 2);
+        let mut values = vec![0.; scheduling_problem.model.vars().len()];
+        for var in scheduling_problem
+            .selections
+            .keys()
+            .chain(scheduling_problem.subject_vars.values())
+        {
+            values[var.index()] = 1.;
+        }
+        assert_eq!(levels[1].evaluate(&values), -2. * 10. + 2.);
+        assert_eq!(scheduling_problem.objective_components.len(), 3);
+
+        let single: ObjectiveSettings =
+            serde_json::from_str(r#""minweekdays""#).expect("should parse a single objective");
+        assert_eq!(single.components().len(), 1);
+
+        for weight in [-1., 0., f64::NAN] {
+            let mut scheduling_problem = SchedulingProblem::new();
+            scheduling_problem.add_courses(CourseSelection::build_from_lectures(vec![
+                Lecture::new("9:30", "11:30", "Monday", "VO", "JO1111", "First", 4.),
+            ]));
+            scheduling_problem.add_subject_indicators();
+            let objectives = ObjectiveSettings::Combined(vec![WeightedObjective {
+                objective: SolutionObjective::MinimizeGaps,
+                weight,
+                priority: 0,
+            }]);
+            assert!(matches!(
+                scheduling_problem.set_objective(&objectives),
+                Err(SchedularError::InvalidConstraint(_))
+            ));
+        }
+    }
+
+    /// Stops every solve at the time limit with the empty schedule.
+    struct TimedOutSolver(Cell<usize>);
+
+    impl Solver for TimedOutSolver {
+        fn solve(
+            &self,
+            model: &Model,
+            _max_num_solutions: usize,
+        ) -> Result<Vec<Solution>, SchedularError> {
+            self.0.set(self.0.get() + 1);
+            let values = vec![0.; model.vars().len()];
+            Ok(vec![Solution::new(model, values).with_optimality(false)])
+        }
+    }
+
+    #[test]
+    fn test_time_limit_stops_at_best_found() {
+        let lectures = vec![
+            Lecture::new("9:30", "11:30", "Monday", "VO", "JO1111", "First", 4.),
+            Lecture::new("12:30", "14:30", "Tuesday", "VO", "NE9999", "Second", 6.),
+        ];
+        let mut scheduling_problem = SchedulingProblem::new();
+        scheduling_problem.add_courses(CourseSelection::build_from_lectures(lectures));
+        scheduling_problem.add_subject_indicators();
+        scheduling_problem.add_weekday_indicators();
+        scheduling_problem
+            .set_objective(&ObjectiveSettings::Combined(vec![
+                WeightedObjective {
+                    objective: SolutionObjective::MaximizeNumEcts,
+                    weight: 1.,
+                    priority: 1,
+                },
+                WeightedObjective {
+                    objective: SolutionObjective::MinimizeNumWeekdays,
+                    weight: 1.,
+                    priority: 0,
+                },
+            ]))
+            .expect("should be able to set combined objectives");
+
+        let reported = Arc::new(Mutex::new(vec![]));
+        let observed = reported.clone();
+        scheduling_problem.set_progress_observer(move |progress| {
+            observed.lock().unwrap().push(progress);
+        });
+
+        let solver = TimedOutSolver(Cell::new(0));
+        let solutions = scheduling_problem
+            .optimize(&solver)
+            .expect("should return the best solution found");
+        // the lower priority level is skipped
+        assert_eq!(solver.0.get(), 1);
+        assert_eq!(solutions.len(), 1);
+        let schedule = scheduling_problem.schedule_from(&solutions[0]);
+        assert_eq!(schedule.status, SolutionStatus::BestFound);
+        assert!(matches!(
+            reported.lock().unwrap().as_slice(),
+            [SolveProgress::Incumbent { .. }]
+        ));
+    }
+
+    #[test]
+    fn test_preference_objective() {
+        let lectures = vec![
+            Lecture::new("9:30", "11:30", "Monday", "VO", "JO1111", "First", 4.),
+            Lecture::new("12:30", "14:30", "Monday", "UE", "JO1111", "First", 2.),
+            Lecture::new("14:30", "16:30", "Tuesday", "UE", "JO1111", "First", 2.),
+            Lecture::new("12:30", "14:30", "Tuesday", "VO", "NE9999", "Second", 6.),
+        ];
+        let mut selections = CourseSelection::build_from_lectures(lectures);
+        let preferences = HashMap::from([("JO1111".to_string(), 3.)]);
+        CourseSelection::apply_preferences(&mut selections, &preferences);
+        let mut scheduling_problem = SchedulingProblem::new();
+        scheduling_problem.add_courses(selections);
+        scheduling_problem
+            .set_objective(&SolutionObjective::MaximizePreference.into())
+            .expect("should be able to set the preference objective");
+
+        // both exercise groups of the rated subject carry its preference
+        let preference = &scheduling_problem.objective_levels[0];
+        assert_eq!(preference.evaluate(&[1., 0., 0.]), -3.);
+        assert_eq!(preference.evaluate(&[0., 1., 0.]), -3.);
+        assert_eq!(preference.evaluate(&[0., 0., 1.]), -1.);
+    }
+
+    #[test]
+    fn test_day_windows_and_max_hours() {
+        let lectures = vec![
+            Lecture::new("8:00", "10:00", "Monday", "VO", "JO1111", "First", 4.),
+            Lecture::new("15:00", "17:00", "Friday", "VO", "NE9999", "Second", 4.),
+            Lecture::new("15:00", "17:00", "Thursday", "VO", "MA0000", "Third", 4.),
+            Lecture::new("10:00", "14:00", "Thursday", "VO", "MA1111", "Fourth", 4.),
+        ];
+        let mut scheduling_problem = SchedulingProblem::new();
+        scheduling_problem.add_courses(CourseSelection::build_from_lectures(lectures));
+        let constraints = ConstraintSettings {
+            day_windows: Some(vec![
+                DayWindow {
+                    weekday: None,
+                    earliest_start: Some(time("10:00")),
+                    latest_end: None,
+                },
+                DayWindow {
+                    weekday: Some("Friday".to_string()),
+                    earliest_start: None,
+                    latest_end: Some(time("16:00")),
+                },
+            ]),
+            max_hours_per_day: Some(5.),
+            ..Default::default()
+        };
+        scheduling_problem
+            .add_constraints(&constraints)
+            .expect("should be able to add constraints");
+
+        // selections keep the order of the lectures: JO1111, NE9999, MA0000, MA1111
+        assert!(!scheduling_problem.model.is_feasible(&[1., 0., 0., 0.]));
+        assert!(!scheduling_problem.model.is_feasible(&[0., 1., 0., 0.]));
+        assert!(scheduling_problem.model.is_feasible(&[0., 0., 1., 0.]));
+        assert!(scheduling_problem.model.is_feasible(&[0., 0., 0., 1.]));
+        assert!(!scheduling_problem.model.is_feasible(&[0., 0., 1., 1.]));
+    }
+
+    #[test]
+    fn test_malformed_day_window() {
+        let mut scheduling_problem = SchedulingProblem::new();
+        let constraints = ConstraintSettings {
+            day_windows: Some(vec![DayWindow {
+                weekday: None,
+                earliest_start: Some(time("16:00")),
+                latest_end: Some(time("10:00")),
+            }]),
+            ..Default::default()
+        };
+        assert!(scheduling_problem.add_constraints(&constraints).is_err());
+    }
+
+    #[test]
+    fn test_day_summaries() {
+        let lectures = vec![
+            Lecture::new("8:00", "10:00", "Monday", "VO", "JO1111", "First", 4.),
+            Lecture::new("12:00", "14:00", "Monday", "VO", "NE9999", "Second", 4.),
+            Lecture::new("13:00", "15:30", "Monday", "VO", "MA0000", "Third", 4.),
+        ];
+        let days =
+            SchedulingProblem::day_summaries(&CourseSelection::build_from_lectures(lectures));
+        assert_eq!(days.len(), 1);
+        assert_eq!(days[0].weekday, "Monday");
+        assert_eq!(days[0].span_minutes, 450);
+        assert_eq!(days[0].idle_minutes, 120);
+    }
+
+    #[test]
+    fn test_min_subject_distance() {
+        let mut scheduling_problem = SchedulingProblem::new();
+        let constraints = ConstraintSettings {
+            min_subject_distance: Some(0),
+            ..Default::default()
+        };
+        assert!(scheduling_problem.add_constraints(&constraints).is_err());
+
+        let constraints = ConstraintSettings {
+            min_subject_distance: Some(2),
+            max_num_solutions: Some(3),
+            ..Default::default()
+        };
+        scheduling_problem
+            .add_constraints(&constraints)
+            .expect("should accept a positive subject distance");
+        assert_eq!(scheduling_problem.min_subject_distance, Some(2));
+
+        let schedules = solve_fixture_with(&[], &[], &constraints, r#""maxects""#)
+            .expect("should find schedules");
+        assert_eq!(schedules.len(), 3);
+        for (first, second) in schedules.iter().tuple_combinations() {
+            let distance = first
+                .subjects
+                .iter()
+                .filter(|subject| !second.subjects.contains(subject))
+                .chain(
+                    second
+                        .subjects
+                        .iter()
+                        .filter(|subject| !first.subjects.contains(subject)),
+                )
+                .count();
+            assert!(distance >= 2);
+        }
+    }
+
+    #[test]
+    fn test_infeasibility_explanation() {
+        use crate::schedular::infeasibility::explain_infeasibility;
+
+        let lectures = vec![
+            Lecture::new("9:30", "11:30", "Monday", "VO", "JO1111", "First", 4.),
+            Lecture::new("12:30", "14:30", "Tuesday", "VO", "NE9999", "Second", 6.),
+        ];
+        let mut scheduling_problem = SchedulingProblem::new();
+        scheduling_problem.add_courses(CourseSelection::build_from_lectures(lectures));
+        scheduling_problem.add_subject_indicators();
+        scheduling_problem.add_weekday_indicators();
+        let mut constraints = constraints_with_blocked_slot("Wednesday", "08:00", "10:00");
+        constraints.min_num_ects = Some(10);
+        constraints.max_num_days = Some(1);
+        scheduling_problem
+            .add_constraints(&constraints)
+            .expect("should be able to add constraints");
+        assert!(NativeSolver::default()
+            .solve(&scheduling_problem.model, 1)
+            .expect("should be able to solve")
+            .is_empty());
+
+        let conflict = explain_infeasibility(
+            &scheduling_problem.model,
+            &scheduling_problem.constraint_groups,
+            &NativeSolver::default(),
+        )
+        .expect("should be able to explain the conflict");
+        assert_eq!(
+            conflict.constraints,
+            vec!["min_num_ects 10", "max_num_days 1"]
+        );
+        assert_eq!(
+            conflict.message,
+            "min_num_ects 10 is unreachable with max_num_days 1; max achievable is 6; \
+             min achievable for max_num_days 1 is 2"
+        );
+    }
+
+    #[test]
+    fn test_soft_constraints() {
+        let lectures = vec![
+            Lecture::new("9:30", "11:30", "Monday", "VO", "JO1111", "First", 4.),
+            Lecture::new("12:30", "14:30", "Tuesday", "VO", "NE9999", "Second", 6.),
+        ];
+        let mut scheduling_problem = SchedulingProblem::new();
+        scheduling_problem.add_courses(CourseSelection::build_from_lectures(lectures));
+        let constraints = ConstraintSettings {
+            min_num_ects: Some(12),
+            soft_constraints: Some(HashMap::from([("min_num_ects".to_string(), 5.)])),
+            ..Default::default()
+        };
+        scheduling_problem
+            .add_constraints(&constraints)
+            .expect("should be able to add constraints");
+        assert!(!scheduling_problem.model.is_feasible(&[1., 1.]));
+        scheduling_problem.soften_constraints();
+
+        // course variables, then the shortfall of the ects
+        assert!(!scheduling_problem.model.is_feasible(&[1., 1., 0.]));
+        assert!(scheduling_problem.model.is_feasible(&[1., 1., 2.]));
+        let solution = Solution::new(&scheduling_problem.model, vec![1., 1., 2.]);
+        let violations = scheduling_problem.violations(&solution);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].message, "2 ECTS short");
+        assert_eq!(scheduling_problem.penalty.evaluate(solution.values()), 10.);
+        assert_eq!(describe_violation("max_num_days", 1.).1, "1 extra day");
+
+        let constraints = ConstraintSettings {
+            soft_constraints: Some(HashMap::from([("target_ects".to_string(), 1.)])),
+            ..Default::default()
+        };
+        assert!(SchedulingProblem::new()
+            .add_constraints(&constraints)
+            .is_err());
+    }
+
+    #[test]
+    fn test_non_dominated_schedules() {
+        let lectures = vec![
+            Lecture::new("9:30", "11:30", "Monday", "VO", "JO1111", "First", 4.),
+            Lecture::new("12:30", "14:30", "Tuesday", "VO", "NE9999", "Second", 6.),
+            Lecture::new("14:30", "16:30", "Tuesday", "VO", "MA0001", "Third", 2.),
+        ];
+        let mut scheduling_problem = SchedulingProblem::new();
+        scheduling_problem.add_courses(CourseSelection::build_from_lectures(lectures));
+        // course variables are JO1111, NE9999 and MA0001
+        let schedules = || {
+            [[1., 0., 0.], [0., 1., 0.], [0., 1., 1.], [1., 1., 1.]]
+                .into_iter()
+                .map(|values| {
+                    let solution = Solution::new(&scheduling_problem.model, values.to_vec());
+                    scheduling_problem.schedule_from(&solution)
+                })
+                .collect_vec()
+        };
+
+        let front = non_dominated(schedules(), false);
+        assert_eq!(front.len(), 2);
+        assert_eq!(front[0].subjects, vec!["MA0001", "NE9999"]);
+        assert_eq!(front[1].total_ects, 12.);
+
+        // with only two hours on Tuesday NE9999 alone is not dominated anymore
+        let front = non_dominated(schedules(), true);
+        assert_eq!(front.len(), 3);
+        assert_eq!(front[0].subjects, vec!["NE9999"]);
+    }
+
+    #[test]
+    fn test_pareto_sweep() {
+        use crate::schedular::settings::ParetoSettings;
+
+        let lectures = vec![
+            Lecture::new("9:30", "11:30", "Monday", "VO", "JO1111", "First", 4.),
+            Lecture::new("12:30", "14:30", "Tuesday", "VO", "NE9999", "Second", 6.),
+            Lecture::new("14:30", "16:30", "Tuesday", "VO", "MA0001", "Third", 2.),
+        ];
+        let mut scheduling_problem = SchedulingProblem::new();
+        scheduling_problem.add_courses(CourseSelection::build_from_lectures(lectures));
+        scheduling_problem.add_subject_indicators();
+        scheduling_problem.add_weekday_indicators();
+        let constraints = ConstraintSettings::default();
+        scheduling_problem
+            .add_constraints(&constraints)
+            .expect("should be able to add constraints");
+
+        let front = scheduling_problem
+            .sweep_pareto_front(
+                &NativeSolver::default(),
+                &constraints,
+                &ParetoSettings::default(),
+            )
+            .expect("should be able to sweep the front");
+        let points = front
+            .iter()
+            .map(|schedule| (schedule.num_weekdays, schedule.total_ects))
+            .collect_vec();
+        assert_eq!(points, vec![(1, 8.), (2, 12.)]);
+
+        let constraints = ConstraintSettings {
+            max_num_days: Some(1),
+            soft_constraints: Some(HashMap::from([("max_num_days".to_string(), 1.)])),
+            ..Default::default()
+        };
+        let lectures = vec![
+            Lecture::new("9:30", "11:30", "Monday", "VO", "JO1111", "First", 4.),
+            Lecture::new("12:30", "14:30", "Tuesday", "VO", "NE9999", "Second", 6.),
+            Lecture::new("14:30", "16:30", "Tuesday", "VO", "MA0001", "Third", 2.),
+        ];
+        let mut scheduling_problem = SchedulingProblem::new();
+        scheduling_problem.add_courses(CourseSelection::build_from_lectures(lectures));
+        scheduling_problem.add_subject_indicators();
+        scheduling_problem.add_weekday_indicators();
+        scheduling_problem
+            .add_constraints(&constraints)
+            .expect("should be able to add constraints");
+        scheduling_problem.soften_constraints();
+
+        // the soft day limit does not restrict the range of the sweep
+        let front = scheduling_problem
+            .sweep_pareto_front(
+                &NativeSolver::default(),
+                &constraints,
+                &ParetoSettings::default(),
+            )
+            .expect("should be able to sweep the front");
+        let points = front
+            .iter()
+            .map(|schedule| (schedule.num_weekdays, schedule.total_ects))
+            .collect_vec();
+        assert_eq!(points, vec![(1, 8.), (2, 12.)]);
+    }
+
+    #[test]
+    fn test_locked_and_banned_selections() {
+        let lectures = [
+            ("101", "9:30", "11:30", "Monday", "VO", "JO1111"),
+            ("102", "12:30", "14:30", "Monday", "UE", "JO1111"),
+            ("103", "14:30", "16:30", "Tuesday", "UE", "JO1111"),
+            ("201", "12:30", "14:30", "Tuesday", "VO", "NE9999"),
+        ]
+        .into_iter()
+        .map(|(id, from, to, weekday, course_type, subject)| Lecture {
+            id: id.to_string(),
+            ..Lecture::new(from, to, weekday, course_type, subject, "Name", 4.)
+        })
+        .collect_vec();
+        let selections = CourseSelection::build_from_lectures(lectures);
+        assert_eq!(selections[1].id, "JO1111/101/103@Tuesday-14:30");
+        assert_eq!(selections[1].appointments[1].lecture_id, "103");
+        let mut scheduling_problem = SchedulingProblem::new();
+        let constraints = ConstraintSettings {
+            locked_selections: Some(vec![selections[1].id.clone()]),
+            banned_selections: Some(vec![selections[2].id.clone()]),
+            ..Default::default()
+        };
+        scheduling_problem.add_courses(selections);
+        scheduling_problem
+            .add_constraints(&constraints)
+            .expect("should be able to lock and ban selections");
+
+        assert!(scheduling_problem.model.is_feasible(&[0., 1., 0.]));
+        assert!(!scheduling_problem.model.is_feasible(&[1., 0., 0.]));
+        assert!(!scheduling_problem.model.is_feasible(&[0., 1., 1.]));
+
+        let constraints = ConstraintSettings {
+            locked_selections: Some(vec!["MA0000/301/".to_string()]),
+            ..Default::default()
+        };
+        assert!(scheduling_problem.add_constraints(&constraints).is_err());
+
+        // exercise groups without distinct lecture ids still get distinct selection ids
+        let selections = CourseSelection::build_from_lectures(week_lectures());
+        assert!(selections
+            .iter()
+            .map(|selection| &selection.id)
+            .all_unique());
+
+        let mut scheduling_problem = SchedulingProblem::new();
+        let banned = selections[0].id.clone();
+        scheduling_problem.add_courses([selections[0].clone(), selections[0].clone()]);
+        let constraints = ConstraintSettings {
+            banned_selections: Some(vec![banned]),
+            ..Default::default()
+        };
+        scheduling_problem
+            .add_constraints(&constraints)
+            .expect("should ban a selection added twice");
+        assert!(scheduling_problem.model.is_feasible(&[0., 0.]));
+        assert!(!scheduling_problem.model.is_feasible(&[0., 1.]));
+    }
+
+    #[test]
+    fn test_malformed_blocked_slot() {
+        let mut scheduling_problem = SchedulingProblem::new();
+        let constraints = constraints_with_blocked_slot("Monday", "12:00", "11:00");
+        assert!(scheduling_problem.add_constraints(&constraints).is_err());
+    }
+}
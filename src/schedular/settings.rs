@@ -1,25 +1,247 @@
-use serde::Deserialize;
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, env, str::FromStr, time::Duration};
+
+use super::{
+    solver::{default_solver, SolveLimits, Solver, SolverBackend},
+    SchedularError,
+};
+
+/// Reads a numeric setting of the server from the environment.
+pub fn env_or<T: FromStr>(key: &str, default: T) -> Result<T, SchedularError> {
+    match env::var(key) {
+        Ok(value) => value.parse().map_err(|_| {
+            SchedularError::InvalidConfiguration(format!(
+                "{} should be a number, got `{}`",
+                key, value
+            ))
+        }),
+        Err(_) => Ok(default),
+    }
+}
+
+/// Server side upper bounds for the solver settings of a request, requests without
+/// a time limit get the maximum one.
+#[derive(Debug, Clone, Copy)]
+pub struct SolverCaps {
+    pub max_time_limit_secs: f64,
+    pub max_mip_gap: f64,
+}
+
+impl Default for SolverCaps {
+    fn default() -> Self {
+        Self {
+            max_time_limit_secs: 60.,
+            max_mip_gap: 0.1,
+        }
+    }
+}
+
+impl SolverCaps {
+    /// Configured by `SOLVER_MAX_TIME_LIMIT_SECS` and `SOLVER_MAX_MIP_GAP`.
+    pub fn from_env() -> Result<Self, SchedularError> {
+        let defaults = Self::default();
+        let caps = Self {
+            max_time_limit_secs: env_or(
+                "SOLVER_MAX_TIME_LIMIT_SECS",
+                defaults.max_time_limit_secs,
+            )?,
+            max_mip_gap: env_or("SOLVER_MAX_MIP_GAP", defaults.max_mip_gap)?,
+        };
+        if !caps.max_time_limit_secs.is_finite() || caps.max_time_limit_secs <= 0. {
+            return Err(SchedularError::InvalidConfiguration(format!(
+                "SOLVER_MAX_TIME_LIMIT_SECS has to be positive, got {}",
+                caps.max_time_limit_secs
+            )));
+        }
+        if !caps.max_mip_gap.is_finite() || caps.max_mip_gap < 0. {
+            return Err(SchedularError::InvalidConfiguration(format!(
+                "SOLVER_MAX_MIP_GAP must not be negative, got {}",
+                caps.max_mip_gap
+            )));
+        }
+        Ok(caps)
+    }
+}
 
 type CoursesPerFaculty = Vec<(String, i32)>;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 pub struct ConstraintSettings {
     pub min_num_ects: Option<i32>,
+    pub max_num_ects: Option<i32>,
+    pub target_ects: Option<EctsRange>,
     pub max_num_solutions: Option<i32>,
+    /// Minimum number of subjects in which any two returned solutions differ
+    pub min_subject_distance: Option<i32>,
     pub max_num_days: Option<i32>,
     pub max_courses_per_faculty: Option<CoursesPerFaculty>,
+    pub blocked_slots: Option<Vec<TimeSlot>>,
+    pub min_free_days: Option<i32>,
+    pub free_hours: Option<FreeHours>,
+    pub day_windows: Option<Vec<DayWindow>>,
+    pub max_hours_per_day: Option<f64>,
+    /// Ids of course selections that have to be part of every schedule
+    pub locked_selections: Option<Vec<String>>,
+    /// Ids of course selections that must not be part of any schedule
+    pub banned_selections: Option<Vec<String>>,
+    /// Penalty weight per setting name, these settings may be violated at that cost
+    pub soft_constraints: Option<HashMap<String, f64>>,
+}
+
+/// Courses have to take place inside this window, on `weekday` or on every day if it is not set.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DayWindow {
+    pub weekday: Option<String>,
+    pub earliest_start: Option<NaiveTime>,
+    pub latest_end: Option<NaiveTime>,
+}
+
+/// Desired amount of ECTS, deviations are penalized by the `ectstarget` objective.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EctsRange {
+    pub min: f64,
+    pub max: f64,
+}
+
+/// Keep at least `hours` free between `from` and `to`, summed over all weekdays.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FreeHours {
+    pub hours: f64,
+    pub from: NaiveTime,
+    pub to: NaiveTime,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TimeSlot {
+    pub weekday: String,
+    pub from: NaiveTime,
+    pub to: NaiveTime,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ParetoSettings {
+    /// Bounds on the contact hours per day to sweep in addition to the number of weekdays
+    pub max_hours_per_day: Option<Vec<f64>>,
+}
+
+/// How long and how exactly the solver works on a request.
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+pub struct SolverSettings {
+    pub time_limit_secs: Option<f64>,
+    /// Relative gap between the best schedule and the bound at which the solver may stop
+    pub mip_gap: Option<f64>,
+    /// Backend to solve with, the server's default one if not given
+    pub solver: Option<SolverBackend>,
 }
 
-#[derive(Debug)]
+impl SolverSettings {
+    /// The limits of the request, capped at the configured maxima.
+    /// Also fails if the requested backend is not part of this build.
+    pub fn limits(&self, caps: &SolverCaps) -> Result<SolveLimits, SchedularError> {
+        if let Some(backend) = self.solver.filter(|backend| !backend.is_available()) {
+            return Err(SchedularError::InvalidSolverSettings(format!(
+                "the {:?} solver is not available",
+                backend
+            )));
+        }
+        let time_limit_secs = self.time_limit_secs.unwrap_or(caps.max_time_limit_secs);
+        if time_limit_secs <= 0. {
+            return Err(SchedularError::InvalidSolverSettings(format!(
+                "time_limit_secs has to be positive, got {}",
+                time_limit_secs
+            )));
+        }
+        if let Some(mip_gap) = self.mip_gap.filter(|mip_gap| *mip_gap < 0.) {
+            return Err(SchedularError::InvalidSolverSettings(format!(
+                "mip_gap must not be negative, got {}",
+                mip_gap
+            )));
+        }
+        let time_limit = Duration::from_secs_f64(time_limit_secs.min(caps.max_time_limit_secs));
+        let mip_gap = self.mip_gap.map(|mip_gap| mip_gap.min(caps.max_mip_gap));
+        Ok(SolveLimits::new(Some(time_limit), mip_gap))
+    }
+
+    pub fn solver(&self, limits: SolveLimits) -> Box<dyn Solver> {
+        match self.solver {
+            Some(backend) => backend.solver(limits),
+            None => default_solver(limits),
+        }
+    }
+}
+
+/// A requested course, either just its code or together with a preference rating.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum CourseRequest {
+    Code(String),
+    Rated { subject: String, preference: f64 },
+}
+
+impl CourseRequest {
+    pub fn subject(&self) -> &String {
+        match self {
+            CourseRequest::Code(subject) | CourseRequest::Rated { subject, .. } => subject,
+        }
+    }
+
+    pub fn preference(&self) -> Option<f64> {
+        match self {
+            CourseRequest::Code(_) => None,
+            CourseRequest::Rated { preference, .. } => Some(*preference),
+        }
+    }
+}
+
+/// The courses a request chooses from, as they are sent to the server.
+#[derive(Deserialize, Debug)]
+pub struct CourseFilter {
+    courses: Vec<CourseRequest>,
+    #[serde(default)]
+    required_courses: Vec<String>,
+    #[serde(default)]
+    excluded_courses: Vec<String>,
+    curriculum: String,
+    semester: String,
+}
+
+impl CourseFilter {
+    pub fn with_filter_settings<T>(&self, f: impl FnOnce(FilterSettings) -> T) -> T {
+        let codes = self
+            .courses
+            .iter()
+            .map(|course| course.subject().to_owned())
+            .collect();
+        let preferences = self
+            .courses
+            .iter()
+            .filter_map(|course| Some((course.subject().to_owned(), course.preference()?)))
+            .collect();
+        f(FilterSettings {
+            courses: Some(&codes),
+            semester: Some(&self.semester),
+            excluded_courses: Some(&self.excluded_courses),
+            required_courses: Some(&self.required_courses),
+            preferences: Some(&preferences),
+            faculties: None,
+            curriculum: Some(&self.curriculum),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
 pub struct FilterSettings<'a> {
     pub semester: Option<&'a str>,
     pub courses: Option<&'a Vec<String>>,
     pub excluded_courses: Option<&'a Vec<String>>,
+    pub required_courses: Option<&'a Vec<String>>,
+    pub preferences: Option<&'a HashMap<String, f64>>,
     pub faculties: Option<&'a Vec<String>>,
     pub curriculum: Option<&'a str>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SolutionObjective {
     #[serde(rename = "noobjective")]
     NoObjective,
@@ -29,4 +251,100 @@ pub enum SolutionObjective {
     MinimizeNumWeekdays,
     #[serde(rename = "maxects")]
     MaximizeNumEcts,
+    #[serde(rename = "ectstarget")]
+    MinimizeEctsDeviation,
+    #[serde(rename = "mingaps")]
+    MinimizeGaps,
+    #[serde(rename = "minspan")]
+    MinimizeSpan,
+    #[serde(rename = "maxpreference")]
+    MaximizePreference,
+}
+
+/// Either a single objective or several ones. Objectives of the same priority are combined
+/// into a weighted sum, different priorities are optimized lexicographically, highest first.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum ObjectiveSettings {
+    Single(SolutionObjective),
+    Combined(Vec<WeightedObjective>),
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct WeightedObjective {
+    pub objective: SolutionObjective,
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+    #[serde(default)]
+    pub priority: i32,
+}
+
+fn default_weight() -> f64 {
+    1.
+}
+
+impl ObjectiveSettings {
+    pub fn components(&self) -> Vec<WeightedObjective> {
+        match self {
+            ObjectiveSettings::Single(objective) => vec![WeightedObjective {
+                objective: *objective,
+                weight: default_weight(),
+                priority: 0,
+            }],
+            ObjectiveSettings::Combined(objectives) => objectives.clone(),
+        }
+    }
+}
+
+impl From<SolutionObjective> for ObjectiveSettings {
+    fn from(objective: SolutionObjective) -> Self {
+        ObjectiveSettings::Single(objective)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{env, time::Duration};
+
+    use crate::schedular::SchedularError;
+
+    use super::{env_or, SolverCaps, SolverSettings};
+
+    #[test]
+    fn test_solver_limits_are_capped() {
+        let caps = SolverCaps {
+            max_time_limit_secs: 30.,
+            max_mip_gap: 0.1,
+        };
+        let settings = SolverSettings {
+            time_limit_secs: Some(600.),
+            mip_gap: Some(0.5),
+            solver: None,
+        };
+        let limits = settings.limits(&caps).unwrap();
+        assert!(limits.remaining_time().unwrap() <= Duration::from_secs(30));
+        assert_eq!(limits.mip_gap(), Some(0.1));
+
+        let limits = SolverSettings::default().limits(&caps).unwrap();
+        assert!(limits.remaining_time().unwrap() > Duration::from_secs(20));
+        assert_eq!(limits.mip_gap(), None);
+
+        let negative = SolverSettings {
+            time_limit_secs: Some(-1.),
+            ..Default::default()
+        };
+        assert!(negative.limits(&caps).is_err());
+    }
+
+    #[test]
+    fn test_malformed_environment() {
+        env::set_var("TEST_ENV_OR_MALFORMED", "ten");
+        assert!(matches!(
+            env_or("TEST_ENV_OR_MALFORMED", 10),
+            Err(SchedularError::InvalidConfiguration(_))
+        ));
+        env::set_var("TEST_ENV_OR_NUMBER", "12");
+        assert_eq!(env_or("TEST_ENV_OR_NUMBER", 10).unwrap(), 12);
+        assert_eq!(env_or("TEST_ENV_OR_MISSING", 10).unwrap(), 10);
+    }
 }
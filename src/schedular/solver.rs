@@ -0,0 +1,628 @@
+use itertools::Itertools;
+use serde::Deserialize;
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use super::SchedularError;
+
+#[cfg(feature = "gurobi")]
+pub mod gurobi;
+#[cfg(feature = "highs")]
+pub mod highs;
+pub mod lns;
+pub mod native;
+
+const EPSILON: f64 = 1e-6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Var(usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VarType {
+    Binary,
+    Integer,
+    Continuous,
+}
+
+#[derive(Debug, Clone)]
+pub struct VarDefinition {
+    pub name: String,
+    pub var_type: VarType,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LinExpr {
+    terms: Vec<(f64, Var)>,
+    constant: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConstrSense {
+    Less,
+    Greater,
+    Equal,
+}
+
+#[derive(Debug, Clone)]
+pub struct Constraint {
+    pub name: String,
+    pub expr: LinExpr,
+    pub sense: ConstrSense,
+    pub rhs: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelSense {
+    Minimize,
+    Maximize,
+}
+
+/// Solver independent description of a mixed integer linear program.
+/// Backends translate it into their native representation when solving.
+#[derive(Debug, Clone)]
+pub struct Model {
+    name: String,
+    vars: Vec<VarDefinition>,
+    constraints: Vec<Constraint>,
+    objective: LinExpr,
+    sense: ModelSense,
+    /// Variables that make up one decision, e.g. the variants of a subject.
+    /// Heuristics change a solution one group at a time.
+    decision_groups: Vec<Vec<Var>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Solution {
+    objective_value: f64,
+    values: Vec<f64>,
+    /// False if the solver stopped at a limit before proving optimality
+    optimal: bool,
+    /// Best known bound on the optimal objective, if the solver reports one
+    bound: Option<f64>,
+}
+
+/// Bounds on the work of all solver calls of one request. The deadline is
+/// fixed on creation, so lexicographic levels and re-solves share one budget.
+#[derive(Debug, Clone, Default)]
+pub struct SolveLimits {
+    time_limit: Option<Duration>,
+    deadline: Option<Instant>,
+    mip_gap: Option<f64>,
+    cancelled: Arc<AtomicBool>,
+}
+
+/// Cancels the solve it belongs to when dropped, e.g. together with the
+/// request handler of a client that disconnected.
+pub struct CancelOnDrop(Arc<AtomicBool>);
+
+/// Intermediate results of a running solve.
+#[derive(Debug, Clone)]
+pub enum SolverProgress {
+    /// A new best solution, not yet proven optimal
+    Incumbent(Solution),
+    /// The objective of the incumbent and the best bound on the optimum
+    Bound { objective: f64, bound: f64 },
+}
+
+/// The solver a request asks for instead of the default one.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SolverBackend {
+    Native,
+    Highs,
+    Gurobi,
+    /// Large neighbourhood search, for models too large to be solved exactly
+    Heuristic,
+}
+
+pub trait Solver {
+    /// Returns up to `max_num_solutions` distinct solutions of `model`, best first.
+    /// An empty vector means that the model is infeasible.
+    fn solve(
+        &self,
+        model: &Model,
+        max_num_solutions: usize,
+    ) -> Result<Vec<Solution>, SchedularError>;
+
+    /// Like `solve`, but reports progress to `observer` while solving. Backends without
+    /// callbacks only report the best solution once they are done.
+    fn solve_observed(
+        &self,
+        model: &Model,
+        max_num_solutions: usize,
+        observer: &dyn Fn(SolverProgress),
+    ) -> Result<Vec<Solution>, SchedularError> {
+        let solutions = self.solve(model, max_num_solutions)?;
+        if let Some(best) = solutions.first() {
+            observer(SolverProgress::Incumbent(best.clone()));
+        }
+        Ok(solutions)
+    }
+}
+
+impl Var {
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+impl LinExpr {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn sum<'a, I: IntoIterator<Item = &'a Var>>(vars: I) -> Self {
+        let mut expr = Self::new();
+        for var in vars {
+            expr.add_term(1., *var);
+        }
+        expr
+    }
+
+    pub fn add_term(&mut self, coeff: f64, var: Var) {
+        self.terms.push((coeff, var));
+    }
+
+    pub fn add_constant(&mut self, constant: f64) {
+        self.constant += constant;
+    }
+
+    pub fn add_expr(&mut self, coeff: f64, other: &LinExpr) {
+        for &(other_coeff, var) in other.terms.iter() {
+            self.add_term(coeff * other_coeff, var);
+        }
+        self.constant += coeff * other.constant;
+    }
+
+    pub fn terms(&self) -> impl Iterator<Item = &(f64, Var)> {
+        self.terms.iter()
+    }
+
+    pub fn constant(&self) -> f64 {
+        self.constant
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn evaluate(&self, values: &[f64]) -> f64 {
+        self.terms.iter().fold(self.constant, |acc, (coeff, var)| {
+            acc + coeff * values[var.0]
+        })
+    }
+}
+
+impl SolveLimits {
+    pub fn new(time_limit: Option<Duration>, mip_gap: Option<f64>) -> Self {
+        Self {
+            time_limit,
+            deadline: time_limit.map(|time_limit| Instant::now() + time_limit),
+            mip_gap,
+            cancelled: Arc::default(),
+        }
+    }
+
+    /// Limits that end after `time_limit` at the latest and are cancelled together with these.
+    pub fn capped(&self, time_limit: Duration) -> Self {
+        let capped = Instant::now() + time_limit;
+        Self {
+            time_limit: Some(
+                self.time_limit
+                    .map_or(time_limit, |own| own.min(time_limit)),
+            ),
+            deadline: Some(self.deadline.map_or(capped, |own| own.min(capped))),
+            mip_gap: self.mip_gap,
+            cancelled: self.cancelled.clone(),
+        }
+    }
+
+    /// Restarts the time limit, e.g. once a queued solve gets its worker.
+    pub fn start_now(self) -> Self {
+        Self {
+            deadline: self
+                .time_limit
+                .map(|time_limit| Instant::now() + time_limit),
+            ..self
+        }
+    }
+
+    pub fn remaining_time(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    pub fn mip_gap(&self) -> Option<f64> {
+        self.mip_gap
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub fn cancel_on_drop(&self) -> CancelOnDrop {
+        CancelOnDrop(self.cancelled.clone())
+    }
+
+    /// Fails if the solve was cancelled or ran out of time before starting another solver call.
+    pub fn check(&self) -> Result<(), SchedularError> {
+        if self.is_cancelled() {
+            return Err(SchedularError::Cancelled);
+        }
+        match self.remaining_time() {
+            Some(remaining) if remaining.is_zero() => Err(SchedularError::TimeLimitReached),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Constraint {
+    pub fn is_satisfied(&self, values: &[f64]) -> bool {
+        let lhs = self.expr.evaluate(values);
+        match self.sense {
+            ConstrSense::Less => lhs <= self.rhs + EPSILON,
+            ConstrSense::Greater => lhs >= self.rhs - EPSILON,
+            ConstrSense::Equal => (lhs - self.rhs).abs() <= EPSILON,
+        }
+    }
+}
+
+impl Model {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            vars: vec![],
+            constraints: vec![],
+            objective: LinExpr::new(),
+            sense: ModelSense::Minimize,
+            decision_groups: vec![],
+        }
+    }
+
+    pub fn add_var(&mut self, name: &str, var_type: VarType, lower: f64, upper: f64) -> Var {
+        self.vars.push(VarDefinition {
+            name: name.to_owned(),
+            var_type,
+            lower,
+            upper,
+        });
+        Var(self.vars.len() - 1)
+    }
+
+    pub fn add_binary(&mut self, name: &str) -> Var {
+        self.add_var(name, VarType::Binary, 0., 1.)
+    }
+
+    /// Restricts the variable to `value`.
+    pub fn fix_var(&mut self, var: Var, value: f64) {
+        self.vars[var.0].lower = value;
+        self.vars[var.0].upper = value;
+    }
+
+    pub fn add_decision_group(&mut self, vars: Vec<Var>) {
+        self.decision_groups.push(vars);
+    }
+
+    pub fn add_constr(&mut self, name: &str, expr: LinExpr, sense: ConstrSense, rhs: f64) {
+        // constants are moved to the right hand side so backends only see variable terms
+        let rhs = rhs - expr.constant;
+        let expr = LinExpr {
+            terms: expr.terms,
+            constant: 0.,
+        };
+        self.constraints.push(Constraint {
+            name: name.to_owned(),
+            expr,
+            sense,
+            rhs,
+        });
+    }
+
+    /// Adds non negative slack to the constraint at `index` so that it can be violated.
+    /// Returns the expression of the violation amount.
+    pub fn relax_constr(&mut self, index: usize) -> LinExpr {
+        let name = self.constraints[index].name.clone();
+        let sense = self.constraints[index].sense;
+        let mut violation = LinExpr::new();
+        if sense != ConstrSense::Greater {
+            let excess = self.add_var(
+                &format!("{}_excess", name),
+                VarType::Continuous,
+                0.,
+                f64::INFINITY,
+            );
+            self.constraints[index].expr.add_term(-1., excess);
+            violation.add_term(1., excess);
+        }
+        if sense != ConstrSense::Less {
+            let shortfall = self.add_var(
+                &format!("{}_shortfall", name),
+                VarType::Continuous,
+                0.,
+                f64::INFINITY,
+            );
+            self.constraints[index].expr.add_term(1., shortfall);
+            violation.add_term(1., shortfall);
+        }
+        violation
+    }
+
+    pub fn set_objective(&mut self, objective: LinExpr, sense: ModelSense) {
+        self.objective = objective;
+        self.sense = sense;
+    }
+
+    /// Cuts off exactly the binary assignment of `solution`, so that re-solving yields
+    /// the next best solution. Used to emulate solution pools on backends without one.
+    pub fn add_no_good_cut(&mut self, solution: &Solution) {
+        let binaries = (0..self.vars.len())
+            .filter(|&index| self.vars[index].var_type == VarType::Binary)
+            .map(Var)
+            .collect_vec();
+        self.add_hamming_cut(&binaries, solution, 1);
+    }
+
+    /// Requires the binary `vars` to differ from their values in `solution`
+    /// in at least `min_distance` positions.
+    pub fn add_hamming_cut(&mut self, vars: &[Var], solution: &Solution, min_distance: usize) {
+        let mut expr = LinExpr::new();
+        let mut num_selected = 0.;
+        for &var in vars {
+            if solution.is_selected(var) {
+                expr.add_term(1., var);
+                num_selected += 1.;
+            } else {
+                expr.add_term(-1., var);
+            }
+        }
+        let name = format!("no_good_{}", self.constraints.len());
+        self.add_constr(
+            &name,
+            expr,
+            ConstrSense::Less,
+            num_selected - min_distance as f64,
+        );
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn vars(&self) -> &[VarDefinition] {
+        &self.vars
+    }
+
+    pub fn constraints(&self) -> &[Constraint] {
+        &self.constraints
+    }
+
+    pub fn objective(&self) -> &LinExpr {
+        &self.objective
+    }
+
+    pub fn sense(&self) -> ModelSense {
+        self.sense
+    }
+
+    pub fn decision_groups(&self) -> &[Vec<Var>] {
+        &self.decision_groups
+    }
+
+    /// Copy of the model without the constraints at the given indices.
+    pub fn without_constraints(&self, indices: &HashSet<usize>) -> Self {
+        let constraints = self
+            .constraints
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !indices.contains(index))
+            .map(|(_, constr)| constr.clone())
+            .collect_vec();
+        Self {
+            constraints,
+            ..self.clone()
+        }
+    }
+
+    pub fn is_feasible(&self, values: &[f64]) -> bool {
+        self.constraints.iter().all(|c| c.is_satisfied(values))
+    }
+}
+
+impl Solution {
+    pub fn new(model: &Model, values: Vec<f64>) -> Self {
+        let values = model
+            .vars
+            .iter()
+            .zip(values)
+            .map(|(definition, value)| match definition.var_type {
+                VarType::Binary | VarType::Integer => value.round(),
+                VarType::Continuous => value,
+            })
+            .collect_vec();
+        let objective_value = model.objective.evaluate(&values);
+        Self {
+            objective_value,
+            values,
+            optimal: true,
+            bound: None,
+        }
+    }
+
+    pub fn with_optimality(self, optimal: bool) -> Self {
+        Self { optimal, ..self }
+    }
+
+    pub fn with_bound(self, bound: f64) -> Self {
+        Self {
+            bound: Some(bound),
+            ..self
+        }
+    }
+
+    /// Relative gap between the objective and the bound, if there is a bound.
+    pub fn gap(&self) -> Option<f64> {
+        self.bound
+            .map(|bound| relative_gap(self.objective_value, bound))
+    }
+
+    pub fn is_optimal(&self) -> bool {
+        self.optimal
+    }
+
+    pub fn objective_value(&self) -> f64 {
+        self.objective_value
+    }
+
+    pub fn value(&self, var: Var) -> f64 {
+        self.values[var.0]
+    }
+
+    pub fn is_selected(&self, var: Var) -> bool {
+        self.values[var.0] > 0.5
+    }
+
+    pub fn values(&self) -> &[f64] {
+        &self.values
+    }
+}
+
+/// Relative gap between an objective value and a bound on it.
+pub fn relative_gap(objective: f64, bound: f64) -> f64 {
+    (objective - bound).abs() / objective.abs().max(1e-10)
+}
+
+#[cfg(feature = "gurobi")]
+pub fn default_solver(limits: SolveLimits) -> Box<dyn Solver> {
+    Box::new(gurobi::GurobiSolver::new(limits))
+}
+
+#[cfg(all(feature = "highs", not(feature = "gurobi")))]
+pub fn default_solver(limits: SolveLimits) -> Box<dyn Solver> {
+    Box::new(highs::HighsSolver::new(limits))
+}
+
+#[cfg(not(any(feature = "highs", feature = "gurobi")))]
+pub fn default_solver(limits: SolveLimits) -> Box<dyn Solver> {
+    Box::new(native::NativeSolver::new(limits))
+}
+
+impl SolverBackend {
+    /// Whether this build includes the backend.
+    pub fn is_available(self) -> bool {
+        match self {
+            SolverBackend::Native | SolverBackend::Heuristic => true,
+            SolverBackend::Highs => cfg!(feature = "highs"),
+            SolverBackend::Gurobi => cfg!(feature = "gurobi"),
+        }
+    }
+
+    /// Falls back to the default solver if the backend is not available.
+    pub fn solver(self, limits: SolveLimits) -> Box<dyn Solver> {
+        match self {
+            SolverBackend::Native => Box::new(native::NativeSolver::new(limits)),
+            SolverBackend::Heuristic => Box::new(lns::LnsSolver::new(limits)),
+            #[cfg(feature = "highs")]
+            SolverBackend::Highs => Box::new(highs::HighsSolver::new(limits)),
+            #[cfg(feature = "gurobi")]
+            SolverBackend::Gurobi => Box::new(gurobi::GurobiSolver::new(limits)),
+            #[allow(unreachable_patterns)]
+            _ => default_solver(limits),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::schedular::SchedularError;
+
+    use super::{ConstrSense, LinExpr, Model, Solution, SolveLimits};
+
+    #[test]
+    fn test_no_good_cut_excludes_solution() {
+        let mut model = Model::new("test");
+        let x = model.add_binary("x");
+        let y = model.add_binary("y");
+        let mut expr = LinExpr::new();
+        expr.add_term(1., x);
+        expr.add_term(1., y);
+        model.add_constr("at_most_one", expr, ConstrSense::Less, 1.);
+
+        let solution = Solution::new(&model, vec![1., 0.]);
+        assert!(model.is_feasible(solution.values()));
+        model.add_no_good_cut(&solution);
+        assert!(!model.is_feasible(&[1., 0.]));
+        assert!(model.is_feasible(&[0., 1.]));
+        assert!(model.is_feasible(&[0., 0.]));
+    }
+
+    #[test]
+    fn test_hamming_cut_keeps_distance() {
+        let mut model = Model::new("test");
+        let vars = (0..3)
+            .map(|num| model.add_binary(&format!("x{}", num)))
+            .collect::<Vec<_>>();
+        let solution = Solution::new(&model, vec![1., 1., 0.]);
+        model.add_hamming_cut(&vars, &solution, 2);
+        assert!(!model.is_feasible(&[1., 1., 0.]));
+        assert!(!model.is_feasible(&[1., 1., 1.]));
+        assert!(model.is_feasible(&[0., 0., 0.]));
+        assert!(model.is_feasible(&[1., 0., 1.]));
+    }
+
+    #[test]
+    fn test_relaxed_constraint() {
+        let mut model = Model::new("test");
+        let x = model.add_binary("x");
+        let mut expr = LinExpr::new();
+        expr.add_term(1., x);
+        model.add_constr("x_is_one", expr, ConstrSense::Equal, 1.);
+        let violation = model.relax_constr(0);
+        assert!(!model.is_feasible(&[0., 0., 0.]));
+        assert!(model.is_feasible(&[0., 0., 1.]));
+        assert!(model.is_feasible(&[1., 0., 0.]));
+        assert_eq!(violation.evaluate(&[0., 0., 1.]), 1.);
+    }
+
+    #[test]
+    fn test_solve_limits() {
+        assert!(SolveLimits::default().check().is_ok());
+        assert!(matches!(
+            SolveLimits::new(Some(Duration::ZERO), None).check(),
+            Err(SchedularError::TimeLimitReached)
+        ));
+
+        let limits = SolveLimits::new(Some(Duration::from_secs(60)), Some(0.01));
+        assert!(limits.check().is_ok());
+        drop(limits.cancel_on_drop());
+        assert!(matches!(limits.check(), Err(SchedularError::Cancelled)));
+    }
+
+    #[test]
+    fn test_constants_move_to_rhs() {
+        let mut model = Model::new("test");
+        let x = model.add_binary("x");
+        let mut expr = LinExpr::new();
+        expr.add_term(2., x);
+        expr.add_constant(1.);
+        model.add_constr("shifted", expr, ConstrSense::Greater, 3.);
+        assert_eq!(model.constraints()[0].rhs, 2.);
+        assert!(model.is_feasible(&[1.]));
+        assert!(!model.is_feasible(&[0.]));
+    }
+}
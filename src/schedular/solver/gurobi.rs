@@ -0,0 +1,203 @@
+use grb::{
+    attribute::ModelIntAttr::SolCount,
+    callback::{CbResult, Where},
+    parameter::DoubleParam::{MIPGap, TimeLimit},
+    parameter::IntParam::{PoolSearchMode, PoolSolutions, SolutionNumber},
+    prelude::*,
+};
+use lazy_static::lazy_static;
+use std::{cell::Cell, ops::Deref, sync::Mutex};
+
+use crate::schedular::SchedularError;
+
+use super::{
+    ConstrSense, LinExpr, Model as SchedulingModel, ModelSense, Solution, SolveLimits, Solver,
+    SolverProgress, VarType,
+};
+
+lazy_static! {
+    /// Starting an environment checks the license, so idle ones are kept for later solves.
+    static ref IDLE_ENVS: Mutex<Vec<IdleEnv>> = Mutex::new(vec![]);
+}
+
+struct IdleEnv(Env);
+
+// SAFETY: an environment is only used by the thread that took it out of the pool,
+// gurobi merely forbids using one environment from several threads at once
+unsafe impl Send for IdleEnv {}
+
+/// An environment taken from the pool, it is returned when dropped.
+struct PooledEnv(Option<Env>);
+
+impl PooledEnv {
+    fn take() -> Result<Self, grb::Error> {
+        let idle = IDLE_ENVS
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .pop();
+        let env = match idle {
+            Some(IdleEnv(env)) => env,
+            None => Env::new("")?,
+        };
+        Ok(Self(Some(env)))
+    }
+}
+
+impl Deref for PooledEnv {
+    type Target = Env;
+
+    fn deref(&self) -> &Env {
+        self.0
+            .as_ref()
+            .expect("the environment is only taken on drop")
+    }
+}
+
+impl Drop for PooledEnv {
+    fn drop(&mut self) {
+        if let Some(env) = self.0.take() {
+            IDLE_ENVS
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .push(IdleEnv(env));
+        }
+    }
+}
+
+/// Gurobi backend, requires a valid license at runtime.
+/// Uses the native solution pool to find the `n` best solutions.
+#[derive(Default)]
+pub struct GurobiSolver {
+    limits: SolveLimits,
+}
+
+impl GurobiSolver {
+    pub fn new(limits: SolveLimits) -> Self {
+        Self { limits }
+    }
+
+    fn expression(expr: &LinExpr, vars: &[Var]) -> grb::expr::LinExpr {
+        let mut expression = grb::expr::LinExpr::new();
+        for &(coeff, var) in expr.terms() {
+            expression.add_term(coeff, vars[var.index()]);
+        }
+        expression.add_constant(expr.constant());
+        expression
+    }
+
+    fn build(model: &SchedulingModel, env: &Env) -> Result<(Model, Vec<Var>), SchedularError> {
+        let mut grb_model = Model::with_env(model.name(), env)?;
+        let vars = model
+            .vars()
+            .iter()
+            .map(|definition| {
+                let var_type = match definition.var_type {
+                    VarType::Binary => Binary,
+                    VarType::Integer => Integer,
+                    VarType::Continuous => Continuous,
+                };
+                grb_model.add_var(
+                    &definition.name,
+                    var_type,
+                    0.,
+                    definition.lower,
+                    definition.upper,
+                    [],
+                )
+            })
+            .collect::<Result<Vec<Var>, grb::Error>>()?;
+
+        for constr in model.constraints() {
+            let lhs = Self::expression(&constr.expr, &vars);
+            let constraint = match constr.sense {
+                ConstrSense::Less => c!(lhs <= constr.rhs),
+                ConstrSense::Greater => c!(lhs >= constr.rhs),
+                ConstrSense::Equal => c!(lhs == constr.rhs),
+            };
+            grb_model.add_constr(&constr.name, constraint)?;
+        }
+
+        let objective = Self::expression(model.objective(), &vars);
+        match model.sense() {
+            ModelSense::Minimize => grb_model.set_objective(objective, Minimize),
+            ModelSense::Maximize => grb_model.set_objective(objective, Maximize),
+        }?;
+        Ok((grb_model, vars))
+    }
+}
+
+impl Solver for GurobiSolver {
+    fn solve(
+        &self,
+        model: &SchedulingModel,
+        max_num_solutions: usize,
+    ) -> Result<Vec<Solution>, SchedularError> {
+        self.solve_observed(model, max_num_solutions, &|_| {})
+    }
+
+    fn solve_observed(
+        &self,
+        model: &SchedulingModel,
+        max_num_solutions: usize,
+        observer: &dyn Fn(SolverProgress),
+    ) -> Result<Vec<Solution>, SchedularError> {
+        self.limits.check()?;
+        let env = PooledEnv::take()?;
+        let (mut grb_model, vars) = Self::build(model, &env)?;
+        grb_model.set_param(PoolSolutions, max_num_solutions as i32)?;
+        grb_model.set_param(PoolSearchMode, 2)?;
+        if let Some(remaining) = self.limits.remaining_time() {
+            grb_model.set_param(TimeLimit, remaining.as_secs_f64())?;
+        }
+        if let Some(mip_gap) = self.limits.mip_gap() {
+            grb_model.set_param(MIPGap, mip_gap)?;
+        }
+        grb_model.update()?;
+
+        // gurobi polls the callback regularly, which lets a cancellation stop the search
+        let limits = &self.limits;
+        let last_bound = Cell::new((f64::NAN, f64::NAN));
+        let mut callback = |location: Where| -> CbResult {
+            match location {
+                Where::Polling(ctx) if limits.is_cancelled() => ctx.terminate(),
+                Where::MIP(ctx) if limits.is_cancelled() => ctx.terminate(),
+                Where::MIP(ctx) => {
+                    // the MIP callback fires on every node, only changes are reported
+                    let bound = (ctx.obj_best()?, ctx.obj_bnd()?);
+                    if bound != last_bound.get() {
+                        last_bound.set(bound);
+                        observer(SolverProgress::Bound {
+                            objective: bound.0,
+                            bound: bound.1,
+                        });
+                    }
+                }
+                Where::MIPSol(ctx) => {
+                    let values = ctx.get_solution(&vars)?;
+                    let incumbent = Solution::new(model, values).with_optimality(false);
+                    observer(SolverProgress::Incumbent(incumbent));
+                }
+                _ => {}
+            }
+            Ok(())
+        };
+        grb_model.optimize_with_callback(&mut callback)?;
+
+        let status = grb_model.status()?;
+        let solution_count = grb_model.get_attr(SolCount)?;
+        if solution_count == 0 {
+            return match status {
+                Status::Interrupted => Err(SchedularError::Cancelled),
+                Status::TimeLimit => Err(SchedularError::TimeLimitReached),
+                _ => Ok(vec![]),
+            };
+        }
+        (0..solution_count)
+            .map(|index| {
+                grb_model.set_param(SolutionNumber, index)?;
+                let values = grb_model.get_obj_attr_batch(attr::Xn, vars.clone())?;
+                Ok(Solution::new(model, values).with_optimality(status == Status::Optimal))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,123 @@
+use ::highs::HighsModelStatus;
+use good_lp::{
+    highs, variable, Expression, ProblemVariables, ResolutionError, SolverModel, Variable,
+};
+use itertools::Itertools;
+
+use crate::schedular::SchedularError;
+
+use super::{ConstrSense, LinExpr, Model, ModelSense, Solution, SolveLimits, Solver, VarType};
+
+/// Open source MILP backend. HiGHS has no solution pool, so additional solutions
+/// are found by re-solving with a no-good cut for every solution found so far.
+/// HiGHS cannot be interrupted, a cancellation only takes effect before the next
+/// re-solve. A running solve continues until it finishes or hits its time limit.
+#[derive(Default)]
+pub struct HighsSolver {
+    limits: SolveLimits,
+}
+
+impl HighsSolver {
+    pub fn new(limits: SolveLimits) -> Self {
+        Self { limits }
+    }
+
+    fn expression(expr: &LinExpr, vars: &[Variable]) -> Expression {
+        let mut expression = Expression::from(expr.constant());
+        for &(coeff, var) in expr.terms() {
+            expression.add_mul(coeff, vars[var.index()]);
+        }
+        expression
+    }
+
+    fn solve_once(&self, model: &Model) -> Result<Option<Solution>, SchedularError> {
+        self.limits.check()?;
+        let mut problem_vars = ProblemVariables::new();
+        let vars = model
+            .vars()
+            .iter()
+            .map(|definition| {
+                let var = variable()
+                    .name(definition.name.clone())
+                    .bounds(definition.lower..=definition.upper);
+                let var = match definition.var_type {
+                    VarType::Binary | VarType::Integer => var.integer(),
+                    VarType::Continuous => var,
+                };
+                problem_vars.add(var)
+            })
+            .collect_vec();
+
+        let objective = Self::expression(model.objective(), &vars);
+        let unsolved = match model.sense() {
+            ModelSense::Minimize => problem_vars.minimise(objective),
+            ModelSense::Maximize => problem_vars.maximise(objective),
+        };
+        let mut problem = unsolved.using(highs);
+        for constr in model.constraints() {
+            let lhs = Self::expression(&constr.expr, &vars);
+            let constraint = match constr.sense {
+                ConstrSense::Less => lhs.leq(constr.rhs),
+                ConstrSense::Greater => lhs.geq(constr.rhs),
+                ConstrSense::Equal => lhs.eq(constr.rhs),
+            };
+            problem.add_constraint(constraint);
+        }
+
+        // the status is only available from the underlying model, good_lp reports limits as success
+        let mut highs_model = problem.into_inner();
+        highs_model.set_option("output_flag", false);
+        if let Some(remaining) = self.limits.remaining_time() {
+            highs_model.set_option("time_limit", remaining.as_secs_f64());
+        }
+        if let Some(mip_gap) = self.limits.mip_gap() {
+            highs_model.set_option("mip_rel_gap", mip_gap);
+        }
+
+        let solved = highs_model.solve();
+        let optimal = match solved.status() {
+            HighsModelStatus::Optimal => true,
+            HighsModelStatus::ReachedTimeLimit | HighsModelStatus::ReachedIterationLimit => false,
+            HighsModelStatus::Infeasible | HighsModelStatus::UnboundedOrInfeasible => {
+                return Ok(None)
+            }
+            status => {
+                return Err(ResolutionError::Str(format!("HiGHS stopped with {:?}", status)).into())
+            }
+        };
+        let values = solved.get_solution().columns().to_vec();
+        let solution = Solution::new(model, values).with_optimality(optimal);
+        // HiGHS reports values even if it stopped before finding any feasible solution
+        if !optimal && !model.is_feasible(solution.values()) {
+            return Err(SchedularError::TimeLimitReached);
+        }
+        Ok(Some(solution))
+    }
+}
+
+impl Solver for HighsSolver {
+    fn solve(
+        &self,
+        model: &Model,
+        max_num_solutions: usize,
+    ) -> Result<Vec<Solution>, SchedularError> {
+        let mut model = model.clone();
+        let mut solutions: Vec<Solution> = vec![];
+        while solutions.len() < max_num_solutions {
+            let solution = match self.solve_once(&model) {
+                Ok(Some(solution)) => solution,
+                Ok(None) => break,
+                // the solutions found so far are still worth returning
+                Err(SchedularError::TimeLimitReached) if !solutions.is_empty() => break,
+                Err(err) => return Err(err),
+            };
+            let optimal = solution.is_optimal();
+            model.add_no_good_cut(&solution);
+            solutions.push(solution);
+            if !optimal {
+                break;
+            }
+        }
+        Ok(solutions)
+    }
+}
@@ -0,0 +1,442 @@
+use std::time::Duration;
+
+use itertools::Itertools;
+
+use crate::schedular::SchedularError;
+
+use super::{
+    native::NativeSolver, relative_gap, ConstrSense, Model, ModelSense, Solution, SolveLimits,
+    Solver, SolverProgress, Var, VarType,
+};
+
+/// Budget of a search without time limit
+const DEFAULT_TIME_LIMIT: Duration = Duration::from_secs(10);
+/// Share of the budget the exact search for a first solution may take
+const INITIAL_SEARCH_SHARE: f64 = 0.5;
+/// Share of the remaining budget the linear relaxation may take for the bound
+const BOUND_SHARE: f64 = 0.25;
+/// Time a single neighbourhood may be searched for
+const NEIGHBOURHOOD_TIME_LIMIT: Duration = Duration::from_secs(2);
+const INITIAL_NEIGHBOURHOOD_SIZE: usize = 8;
+/// The linear relaxation is only solved for a bound if its dense tableau stays this small
+const MAX_BOUND_TABLEAU_ENTRIES: usize = 4_000_000;
+const IMPROVEMENT_TOLERANCE: f64 = 1e-6;
+const SEED: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// Large neighbourhood search for models that are too large to be solved exactly in time.
+/// Starting from the best solution an exact search finds in part of the time limit, or from
+/// the empty schedule if it finds none, it repeatedly frees a few random decision groups,
+/// fixes the others to the incumbent and searches the rest exactly with the native solver.
+/// It runs until the time limit unless the incumbent is proven optimal. Additional solutions
+/// are the previous incumbents. Solutions carry the bound of the linear relaxation if it can
+/// be solved in part of the remaining time.
+#[derive(Default)]
+pub struct LnsSolver {
+    limits: SolveLimits,
+}
+
+/// Deterministic pseudo random numbers, so that a request explores the same neighbourhoods
+/// every time.
+struct XorShift(u64);
+
+impl XorShift {
+    fn below(&mut self, bound: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % bound as u64) as usize
+    }
+
+    /// `amount` distinct indices below `bound`.
+    fn sample(&mut self, bound: usize, amount: usize) -> Vec<usize> {
+        let mut indices = (0..bound).collect_vec();
+        for num in 0..amount.min(bound) {
+            let other = num + self.below(bound - num);
+            indices.swap(num, other);
+        }
+        indices.truncate(amount);
+        indices
+    }
+}
+
+impl LnsSolver {
+    pub fn new(limits: SolveLimits) -> Self {
+        Self { limits }
+    }
+
+    fn bound(model: &Model, limits: &SolveLimits) -> Option<f64> {
+        let num_rows = model.constraints().len();
+        if num_rows * (model.vars().len() + num_rows) > MAX_BOUND_TABLEAU_ENTRIES {
+            tracing::debug!("Model is too large to compute the bound of its relaxation");
+            return None;
+        }
+        match NativeSolver::relaxation_bound(model, limits) {
+            Ok(bound) => bound,
+            Err(err) => {
+                tracing::debug!("No bound for the relaxation: {}", err);
+                None
+            }
+        }
+    }
+
+    /// The empty schedule, every integer variable at its lower bound and the continuous ones
+    /// solved for. None if it is infeasible.
+    fn empty_start(
+        model: &Model,
+        limits: &SolveLimits,
+    ) -> Result<Option<Solution>, SchedularError> {
+        let mut empty = model.clone();
+        for (index, definition) in model.vars().iter().enumerate() {
+            if definition.var_type != VarType::Continuous {
+                empty.fix_var(Var(index), definition.lower);
+            }
+        }
+        let solution = NativeSolver::new(limits.clone()).solve(&empty, 1)?;
+        Ok(solution.into_iter().next().map(|solution| {
+            Solution::new(model, solution.values().to_vec()).with_optimality(false)
+        }))
+    }
+
+    /// The model's decision groups, or every integer variable on its own if it has none.
+    fn groups(model: &Model) -> Vec<Vec<Var>> {
+        if !model.decision_groups().is_empty() {
+            return model.decision_groups().to_vec();
+        }
+        model
+            .vars()
+            .iter()
+            .enumerate()
+            .filter(|(_, definition)| definition.var_type != VarType::Continuous)
+            .map(|(index, _)| vec![Var(index)])
+            .collect()
+    }
+
+    fn improves(model: &Model, objective: f64, best: f64) -> bool {
+        let tolerance = IMPROVEMENT_TOLERANCE * best.abs().max(1.);
+        match model.sense() {
+            ModelSense::Minimize => objective < best - tolerance,
+            ModelSense::Maximize => objective > best + tolerance,
+        }
+    }
+
+    /// The model with all groups but the freed ones fixed to the incumbent, restricted
+    /// to solutions that improve on it.
+    fn neighbourhood(
+        model: &Model,
+        groups: &[Vec<Var>],
+        freed: &[usize],
+        incumbent: &Solution,
+    ) -> Model {
+        let mut neighbourhood = model.clone();
+        for (index, group) in groups.iter().enumerate() {
+            if freed.contains(&index) {
+                continue;
+            }
+            for &var in group {
+                neighbourhood.fix_var(var, incumbent.value(var));
+            }
+        }
+        let best = incumbent.objective_value();
+        let tolerance = IMPROVEMENT_TOLERANCE * best.abs().max(1.);
+        let (sense, rhs) = match model.sense() {
+            ModelSense::Minimize => (ConstrSense::Less, best - tolerance),
+            ModelSense::Maximize => (ConstrSense::Greater, best + tolerance),
+        };
+        neighbourhood.add_constr("lns_improvement", model.objective().clone(), sense, rhs);
+        neighbourhood
+    }
+
+    /// Whether the incumbent is within the allowed gap of the bound.
+    fn closes_gap(&self, incumbent: &Solution, bound: Option<f64>) -> bool {
+        let allowed = self
+            .limits
+            .mip_gap()
+            .unwrap_or(0.)
+            .max(IMPROVEMENT_TOLERANCE);
+        bound.is_some_and(|bound| relative_gap(incumbent.objective_value(), bound) <= allowed)
+    }
+
+    /// Searches neighbourhoods of the incumbent until the time is up or it is proven optimal.
+    /// Returns all incumbents, the best one last, and whether it is optimal.
+    fn improve(
+        &self,
+        model: &Model,
+        first: Solution,
+        bound: Option<f64>,
+        limits: &SolveLimits,
+        observer: &dyn Fn(SolverProgress),
+    ) -> Result<(Vec<Solution>, bool), SchedularError> {
+        let groups = Self::groups(model);
+        let mut optimal = self.closes_gap(&first, bound);
+        let mut incumbents = vec![first];
+        let mut size = INITIAL_NEIGHBOURHOOD_SIZE.min(groups.len());
+        let mut random = XorShift(SEED);
+        while !optimal {
+            match limits.check() {
+                Err(SchedularError::TimeLimitReached) => break,
+                result => result?,
+            }
+            let incumbent = incumbents
+                .last()
+                .expect("the search starts with an incumbent");
+            let freed = random.sample(groups.len(), size);
+            let neighbourhood = Self::neighbourhood(model, &groups, &freed, incumbent);
+            let search = NativeSolver::new(limits.capped(NEIGHBOURHOOD_TIME_LIMIT));
+            let found = match search.solve(&neighbourhood, 1) {
+                Ok(solutions) => solutions.into_iter().next(),
+                Err(SchedularError::TimeLimitReached) => {
+                    size = (size * 2 / 3).max(1);
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+            let Some(found) = found else {
+                // nothing better in this neighbourhood, larger ones are tried from now on
+                optimal = size == groups.len();
+                size = (size + size / 2 + 1).min(groups.len());
+                continue;
+            };
+            if !found.is_optimal() {
+                size = (size * 2 / 3).max(1);
+            }
+            // the neighbourhood restricts the model, its solutions are checked against the model itself
+            let solution = Solution::new(model, found.values().to_vec()).with_optimality(false);
+            if !model.is_feasible(solution.values())
+                || !Self::improves(
+                    model,
+                    solution.objective_value(),
+                    incumbent.objective_value(),
+                )
+            {
+                tracing::warn!(
+                    "Discarding a neighbourhood solution that does not improve the model"
+                );
+                continue;
+            }
+            observer(SolverProgress::Incumbent(solution.clone()));
+            if let Some(bound) = bound {
+                observer(SolverProgress::Bound {
+                    objective: solution.objective_value(),
+                    bound,
+                });
+            }
+            optimal = self.closes_gap(&solution, bound);
+            incumbents.push(solution);
+        }
+        tracing::debug!(
+            "Local search stopped after {} improvements, optimal: {}",
+            incumbents.len() - 1,
+            optimal
+        );
+        Ok((incumbents, optimal))
+    }
+}
+
+impl Solver for LnsSolver {
+    fn solve(
+        &self,
+        model: &Model,
+        max_num_solutions: usize,
+    ) -> Result<Vec<Solution>, SchedularError> {
+        self.solve_observed(model, max_num_solutions, &|_| {})
+    }
+
+    fn solve_observed(
+        &self,
+        model: &Model,
+        max_num_solutions: usize,
+        observer: &dyn Fn(SolverProgress),
+    ) -> Result<Vec<Solution>, SchedularError> {
+        let limits = match self.limits.remaining_time() {
+            Some(_) => self.limits.clone(),
+            None => self.limits.capped(DEFAULT_TIME_LIMIT),
+        };
+        limits.check()?;
+
+        let budget = limits.remaining_time().unwrap_or(DEFAULT_TIME_LIMIT);
+        let initial_search = NativeSolver::new(limits.capped(budget.mul_f64(INITIAL_SEARCH_SHARE)));
+        let initial = match initial_search.solve_observed(model, max_num_solutions, observer) {
+            Ok(initial) => initial,
+            Err(SchedularError::TimeLimitReached) => {
+                let Some(empty) = Self::empty_start(model, &limits)? else {
+                    return Err(SchedularError::TimeLimitReached);
+                };
+                tracing::debug!("No solution found in time, starting from the empty schedule");
+                observer(SolverProgress::Incumbent(empty.clone()));
+                vec![empty]
+            }
+            Err(err) => return Err(err),
+        };
+        let Some(first) = initial.first() else {
+            return Ok(vec![]);
+        };
+        if first.is_optimal() {
+            let optimum = first.objective_value();
+            return Ok(initial
+                .into_iter()
+                .map(|solution| solution.with_bound(optimum))
+                .collect());
+        }
+
+        let remaining = limits.remaining_time().unwrap_or(DEFAULT_TIME_LIMIT);
+        let bound = Self::bound(model, &limits.capped(remaining.mul_f64(BOUND_SHARE)));
+        let with_bound = |solution: Solution| match bound {
+            Some(bound) => solution.with_bound(bound),
+            None => solution,
+        };
+        let (incumbents, optimal) = self.improve(model, first.clone(), bound, &limits, observer)?;
+        let solutions = incumbents
+            .into_iter()
+            .rev()
+            .take(max_num_solutions)
+            .enumerate()
+            .map(|(rank, solution)| with_bound(solution.with_optimality(optimal && rank == 0)))
+            .collect();
+        Ok(solutions)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::schedular::solver::{
+        native::NativeSolver, ConstrSense, LinExpr, Model, ModelSense, Solution, SolveLimits,
+        Solver,
+    };
+
+    use super::{LnsSolver, XorShift};
+
+    /// Assignment of 12 items to 4 bins of capacity 10, each item to at most one bin.
+    /// Every item is a decision group of its 4 assignment variables.
+    fn assignment_model() -> Model {
+        let weights = [3., 4., 5., 6., 2., 7., 3., 8., 4., 5., 6., 1.];
+        let values = [4., 5., 7., 8., 2., 10., 3., 11., 6., 6., 9., 1.];
+        let mut model = Model::new("assignment");
+        let assignments = (0..weights.len())
+            .map(|item| {
+                (0..4)
+                    .map(|bin| model.add_binary(&format!("x_{}_{}", item, bin)))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let mut objective = LinExpr::new();
+        for (item, bins) in assignments.iter().enumerate() {
+            model.add_constr(
+                &format!("item_{}", item),
+                LinExpr::sum(bins.iter()),
+                ConstrSense::Less,
+                1.,
+            );
+            for &var in bins {
+                objective.add_term(values[item], var);
+            }
+            model.add_decision_group(bins.clone());
+        }
+        for bin in 0..4 {
+            let mut load = LinExpr::new();
+            for (item, bins) in assignments.iter().enumerate() {
+                load.add_term(weights[item], bins[bin]);
+            }
+            model.add_constr(&format!("bin_{}", bin), load, ConstrSense::Less, 10.);
+        }
+        model.set_objective(objective, ModelSense::Maximize);
+        model
+    }
+
+    #[test]
+    fn test_sample_is_distinct() {
+        let mut random = XorShift(1);
+        for amount in 0..=10 {
+            let sample = random.sample(10, amount);
+            assert_eq!(sample.len(), amount);
+            assert!(sample.iter().all(|&index| index < 10));
+            assert_eq!(
+                sample
+                    .iter()
+                    .collect::<std::collections::HashSet<_>>()
+                    .len(),
+                amount
+            );
+        }
+    }
+
+    #[test]
+    fn test_neighbourhoods_improve_to_optimum() {
+        let model = assignment_model();
+        let optimum = NativeSolver::default().solve(&model, 1).unwrap()[0].objective_value();
+        let empty = Solution::new(&model, vec![0.; model.vars().len()]);
+        let limits = SolveLimits::new(Some(Duration::from_secs(10)), None);
+        let solver = LnsSolver::new(limits.clone());
+        let (incumbents, optimal) = solver
+            .improve(&model, empty, None, &limits, &|_| {})
+            .unwrap();
+        assert!(optimal);
+        assert!(incumbents.len() > 1);
+        assert!(incumbents
+            .iter()
+            .all(|solution| model.is_feasible(solution.values())));
+        assert!(incumbents
+            .windows(2)
+            .all(|pair| pair[0].objective_value() < pair[1].objective_value()));
+        let best = incumbents.last().unwrap();
+        assert!((best.objective_value() - optimum).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_solutions_carry_relaxation_bound() {
+        let model = assignment_model();
+        let limits = SolveLimits::new(Some(Duration::from_secs(2)), None);
+        let solutions = LnsSolver::new(limits).solve(&model, 3).unwrap();
+        assert!(!solutions.is_empty() && solutions.len() <= 3);
+        assert!(solutions
+            .iter()
+            .all(|solution| model.is_feasible(solution.values())));
+        assert!(solutions
+            .windows(2)
+            .all(|pair| pair[0].objective_value() >= pair[1].objective_value()));
+        let bound = NativeSolver::relaxation_bound(&model, &SolveLimits::default())
+            .unwrap()
+            .unwrap();
+        assert!(solutions[0].objective_value() <= bound + 1e-6);
+        let gap = solutions[0].gap().unwrap();
+        assert!((0. ..0.2).contains(&gap));
+    }
+
+    #[test]
+    fn test_empty_start() {
+        let model = assignment_model();
+        let empty = LnsSolver::empty_start(&model, &SolveLimits::default())
+            .unwrap()
+            .expect("assigning no item is feasible");
+        assert!(empty.values().iter().all(|&value| value == 0.));
+        assert!(!empty.is_optimal());
+
+        let mut model = assignment_model();
+        let first_item = model.decision_groups()[0].clone();
+        model.add_constr(
+            "first_item",
+            LinExpr::sum(first_item.iter()),
+            ConstrSense::Equal,
+            1.,
+        );
+        assert!(LnsSolver::empty_start(&model, &SolveLimits::default())
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_infeasible_model() {
+        let mut model = Model::new("infeasible");
+        let x = model.add_binary("x");
+        let y = model.add_binary("y");
+        model.add_constr(
+            "both",
+            LinExpr::sum([x, y].iter()),
+            ConstrSense::Greater,
+            3.,
+        );
+        let limits = SolveLimits::new(Some(Duration::from_secs(1)), None);
+        assert!(LnsSolver::new(limits).solve(&model, 1).unwrap().is_empty());
+    }
+}
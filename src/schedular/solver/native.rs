@@ -0,0 +1,766 @@
+use itertools::Itertools;
+
+use crate::schedular::SchedularError;
+
+use super::{
+    ConstrSense, Model, ModelSense, Solution, SolveLimits, Solver, SolverProgress, VarType,
+};
+
+const PIVOT_TOLERANCE: f64 = 1e-9;
+const FEASIBILITY_TOLERANCE: f64 = 1e-7;
+const INTEGRALITY_TOLERANCE: f64 = 1e-6;
+/// Consecutive pivots without progress after which Bland's rule prevents cycling
+const MAX_DEGENERATE_PIVOTS: usize = 50;
+
+/// Exact solver written in Rust without any native dependencies, meant for requests with
+/// a few dozen subjects and for tests. Branch and bound on the integer variables with the
+/// linear relaxations solved by a dense simplex. Like HiGHS it has no solution pool, so
+/// additional solutions are found by re-solving with a no-good cut for every solution.
+#[derive(Default)]
+pub struct NativeSolver {
+    limits: SolveLimits,
+}
+
+enum Relaxation {
+    Optimal { objective: f64, values: Vec<f64> },
+    Infeasible,
+    Unbounded,
+}
+
+/// A subproblem of the search, the model with tightened variable bounds.
+#[derive(Clone)]
+struct Node {
+    lower: Vec<f64>,
+    upper: Vec<f64>,
+    /// Objective of the parent's relaxation, a bound on every solution of the node
+    bound: f64,
+}
+
+/// A constraint over the shifted, not fixed variables.
+struct Row {
+    coeffs: Vec<(usize, f64)>,
+    sense: ConstrSense,
+    rhs: f64,
+}
+
+impl Row {
+    /// Whether the row's slack or surplus cannot start out as its basic column.
+    fn needs_artificial(&self) -> bool {
+        match self.sense {
+            ConstrSense::Less => self.rhs < 0.,
+            ConstrSense::Greater => self.rhs > 0.,
+            ConstrSense::Equal => true,
+        }
+    }
+}
+
+/// Dense simplex tableau. Each row ends with its right hand side, the cost row holds the
+/// reduced costs and the negated objective value. Upper bounds are kept out of the rows:
+/// a column at its upper bound is complemented, i.e. replaced by `upper - column`, so that
+/// every non basic column is zero.
+struct Tableau {
+    rows: Vec<Vec<f64>>,
+    cost: Vec<f64>,
+    basis: Vec<usize>,
+    upper: Vec<f64>,
+    complemented: Vec<bool>,
+}
+
+/// How the value of the entering column is limited.
+enum Step {
+    /// A basic column drops to zero
+    Lower(usize),
+    /// A basic column reaches its upper bound
+    Upper(usize),
+    /// The entering column reaches its own upper bound
+    Flip,
+}
+
+impl Tableau {
+    fn rhs(&self, row: usize) -> f64 {
+        *self.rows[row].last().expect("rows end with the rhs")
+    }
+
+    fn pivot(&mut self, pivot_row: usize, pivot_col: usize) {
+        let pivot = self.rows[pivot_row][pivot_col];
+        for value in self.rows[pivot_row].iter_mut() {
+            *value /= pivot;
+        }
+        // rows are mostly sparse, only the non zero entries of the pivot row change others
+        let pivot_values = self.rows[pivot_row]
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| **value != 0.)
+            .map(|(col, value)| (col, *value))
+            .collect_vec();
+        let others = self
+            .rows
+            .iter_mut()
+            .enumerate()
+            .filter(|(index, _)| *index != pivot_row)
+            .map(|(_, row)| row)
+            .chain(std::iter::once(&mut self.cost));
+        for row in others {
+            let factor = row[pivot_col];
+            if factor.abs() > PIVOT_TOLERANCE {
+                for &(col, pivot_value) in pivot_values.iter() {
+                    row[col] -= factor * pivot_value;
+                }
+            }
+            row[pivot_col] = 0.;
+        }
+        self.basis[pivot_row] = pivot_col;
+    }
+
+    /// Substitutes `upper - column` for the column.
+    fn complement(&mut self, col: usize) {
+        let upper = self.upper[col];
+        for row in self.rows.iter_mut().chain(std::iter::once(&mut self.cost)) {
+            let value = row[col];
+            if value != 0. {
+                *row.last_mut().expect("rows end with the rhs") -= value * upper;
+                row[col] = -value;
+            }
+        }
+        self.complemented[col] = !self.complemented[col];
+    }
+
+    /// Sets the cost row to the reduced costs of `costs` for the current basis.
+    fn price(&mut self, costs: &[f64]) {
+        let mut constant = 0.;
+        self.cost = costs
+            .iter()
+            .zip(self.complemented.iter())
+            .zip(self.upper.iter())
+            .map(|((&cost, &complemented), &upper)| {
+                if complemented {
+                    constant += cost * upper;
+                    -cost
+                } else {
+                    cost
+                }
+            })
+            .collect();
+        self.cost.push(-constant);
+        for (row, &basic) in self.rows.iter().zip(self.basis.iter()) {
+            let factor = self.cost[basic];
+            if factor != 0. {
+                for (value, row_value) in self.cost.iter_mut().zip(row.iter()) {
+                    *value -= factor * row_value;
+                }
+            }
+        }
+    }
+
+    /// The ratio test, the largest step the entering column can take while all basic
+    /// columns stay within their bounds. None if the step is unbounded.
+    fn step(&self, entering: usize) -> Option<(f64, Step)> {
+        let mut best = self.upper[entering]
+            .is_finite()
+            .then_some((self.upper[entering], Step::Flip));
+        for (row, values) in self.rows.iter().enumerate() {
+            let coeff = values[entering];
+            let basic = self.basis[row];
+            let (ratio, step) = if coeff > PIVOT_TOLERANCE {
+                (self.rhs(row).max(0.) / coeff, Step::Lower(row))
+            } else if coeff < -PIVOT_TOLERANCE && self.upper[basic].is_finite() {
+                let room = (self.upper[basic] - self.rhs(row)).max(0.);
+                (room / -coeff, Step::Upper(row))
+            } else {
+                continue;
+            };
+            let better = match &best {
+                None => true,
+                Some((best_ratio, Step::Flip)) => ratio < *best_ratio,
+                Some((best_ratio, Step::Lower(best_row) | Step::Upper(best_row))) => {
+                    ratio < *best_ratio || ratio == *best_ratio && basic < self.basis[*best_row]
+                }
+            };
+            if better {
+                best = Some((ratio, step));
+            }
+        }
+        best
+    }
+
+    /// Minimizes the current costs, only columns below `num_enterable` may enter the basis.
+    /// Returns false if the objective is unbounded, fails once the limits are exceeded.
+    fn minimize(
+        &mut self,
+        num_enterable: usize,
+        limits: &SolveLimits,
+    ) -> Result<bool, SchedularError> {
+        let mut degenerate_pivots = 0;
+        loop {
+            limits.check()?;
+            let candidates = (0..num_enterable).filter(|&col| self.cost[col] < -PIVOT_TOLERANCE);
+            let entering = if degenerate_pivots < MAX_DEGENERATE_PIVOTS {
+                candidates.min_by(|&a, &b| self.cost[a].total_cmp(&self.cost[b]))
+            } else {
+                candidates.min()
+            };
+            let Some(entering) = entering else {
+                return Ok(true);
+            };
+            let Some((ratio, step)) = self.step(entering) else {
+                return Ok(false);
+            };
+            if ratio < FEASIBILITY_TOLERANCE {
+                degenerate_pivots += 1;
+            } else {
+                degenerate_pivots = 0;
+            }
+            match step {
+                Step::Flip => self.complement(entering),
+                Step::Lower(row) => self.pivot(row, entering),
+                Step::Upper(row) => {
+                    // the leaving column continues at its upper bound, i.e. complemented at zero
+                    self.complement(self.basis[row]);
+                    for value in self.rows[row].iter_mut() {
+                        *value = -*value;
+                    }
+                    self.pivot(row, entering);
+                }
+            }
+        }
+    }
+
+    /// Value of the column in the current basic solution.
+    fn value(&self, col: usize) -> f64 {
+        let value = self
+            .basis
+            .iter()
+            .position(|&basic| basic == col)
+            .map_or(0., |row| self.rhs(row));
+        if self.complemented[col] {
+            self.upper[col] - value
+        } else {
+            value
+        }
+    }
+}
+
+impl NativeSolver {
+    pub fn new(limits: SolveLimits) -> Self {
+        Self { limits }
+    }
+
+    /// Solves the linear relaxation of `model` within the given variable bounds with the
+    /// two phase simplex method. The objective is always minimized, see `minimization_costs`.
+    fn relax(
+        model: &Model,
+        costs: &[f64],
+        lower: &[f64],
+        upper: &[f64],
+        limits: &SolveLimits,
+    ) -> Result<Relaxation, SchedularError> {
+        // variables are shifted by their lower bound, fixed ones drop out completely
+        let columns = (0..lower.len())
+            .filter(|&var| upper[var] - lower[var] > FEASIBILITY_TOLERANCE)
+            .collect_vec();
+        let mut column_of = vec![None; lower.len()];
+        for (col, &var) in columns.iter().enumerate() {
+            column_of[var] = Some(col);
+        }
+
+        let mut rows = vec![];
+        for constr in model.constraints() {
+            let mut rhs = constr.rhs;
+            let mut coeffs = vec![];
+            for &(coeff, var) in constr.expr.terms() {
+                rhs -= coeff * lower[var.index()];
+                if let Some(col) = column_of[var.index()] {
+                    coeffs.push((col, coeff));
+                }
+            }
+            if coeffs.is_empty() {
+                let satisfied = match constr.sense {
+                    ConstrSense::Less => rhs > -FEASIBILITY_TOLERANCE,
+                    ConstrSense::Greater => rhs < FEASIBILITY_TOLERANCE,
+                    ConstrSense::Equal => rhs.abs() < FEASIBILITY_TOLERANCE,
+                };
+                if !satisfied {
+                    return Ok(Relaxation::Infeasible);
+                }
+                continue;
+            }
+            rows.push(Row {
+                coeffs,
+                sense: constr.sense,
+                rhs,
+            });
+        }
+
+        // every row gets a slack or surplus column, rows without a feasible slack an artificial one
+        let num_structural = columns.len();
+        let num_slacks = rows
+            .iter()
+            .filter(|row| row.sense != ConstrSense::Equal)
+            .count();
+        let num_artificials = rows.iter().filter(|row| row.needs_artificial()).count();
+        let width = num_structural + num_slacks + num_artificials;
+
+        let mut column_upper = vec![f64::INFINITY; width];
+        for (col, &var) in columns.iter().enumerate() {
+            column_upper[col] = upper[var] - lower[var];
+        }
+        let mut tableau = Tableau {
+            rows: vec![],
+            cost: vec![],
+            basis: vec![],
+            upper: column_upper,
+            complemented: vec![false; width],
+        };
+        let mut next_slack = num_structural;
+        let mut next_artificial = num_structural + num_slacks;
+        for constr in rows {
+            let needs_artificial = constr.needs_artificial();
+            let Row { coeffs, sense, rhs } = constr;
+            let mut row = vec![0.; width + 1];
+            for (col, coeff) in coeffs {
+                row[col] += coeff;
+            }
+            row[width] = rhs;
+            let mut basic = None;
+            match sense {
+                ConstrSense::Less => row[next_slack] = 1.,
+                ConstrSense::Greater => row[next_slack] = -1.,
+                ConstrSense::Equal => {}
+            }
+            if sense != ConstrSense::Equal {
+                if !needs_artificial {
+                    basic = Some(next_slack);
+                }
+                next_slack += 1;
+            }
+            // rows are negated to a non negative rhs, a surplus column then becomes a slack
+            let negate = match sense {
+                ConstrSense::Greater => rhs <= 0.,
+                ConstrSense::Less | ConstrSense::Equal => rhs < 0.,
+            };
+            if negate {
+                for value in row.iter_mut() {
+                    *value = -*value;
+                }
+            }
+            if needs_artificial {
+                row[next_artificial] = 1.;
+                basic = Some(next_artificial);
+                next_artificial += 1;
+            }
+            tableau.rows.push(row);
+            tableau
+                .basis
+                .push(basic.expect("every row has a basic column"));
+        }
+
+        if num_artificials > 0 {
+            let mut phase_one_costs = vec![0.; width + 1];
+            for cost in phase_one_costs[num_structural + num_slacks..width].iter_mut() {
+                *cost = 1.;
+            }
+            tableau.price(&phase_one_costs);
+            tableau.minimize(width, limits)?;
+            if -tableau.cost[width] > FEASIBILITY_TOLERANCE {
+                return Ok(Relaxation::Infeasible);
+            }
+            // artificials left in the basis are zero, they are swapped for any other column
+            for row in 0..tableau.rows.len() {
+                if tableau.basis[row] < num_structural + num_slacks {
+                    continue;
+                }
+                let replacement = (0..num_structural + num_slacks)
+                    .find(|&col| tableau.rows[row][col].abs() > PIVOT_TOLERANCE);
+                if let Some(col) = replacement {
+                    tableau.pivot(row, col);
+                }
+            }
+        }
+
+        let mut phase_two_costs = vec![0.; width + 1];
+        for (col, &var) in columns.iter().enumerate() {
+            phase_two_costs[col] = costs[var];
+        }
+        tableau.price(&phase_two_costs);
+        if !tableau.minimize(num_structural + num_slacks, limits)? {
+            return Ok(Relaxation::Unbounded);
+        }
+
+        let mut values = lower.to_vec();
+        for (col, &var) in columns.iter().enumerate() {
+            values[var] += tableau.value(col);
+        }
+        let objective = values
+            .iter()
+            .zip(costs.iter())
+            .map(|(value, cost)| value * cost)
+            .sum();
+        Ok(Relaxation::Optimal { objective, values })
+    }
+
+    /// Optimum of the linear relaxation in terms of the model's objective, a bound on every
+    /// solution. None if the relaxation is infeasible or unbounded, fails once the limits
+    /// are exceeded.
+    pub fn relaxation_bound(
+        model: &Model,
+        limits: &SolveLimits,
+    ) -> Result<Option<f64>, SchedularError> {
+        let lower = model.vars().iter().map(|var| var.lower).collect_vec();
+        let upper = model.vars().iter().map(|var| var.upper).collect_vec();
+        if lower.iter().any(|lower| !lower.is_finite()) {
+            return Ok(None);
+        }
+        let costs = Self::minimization_costs(model);
+        let bound = match Self::relax(model, &costs, &lower, &upper, limits)? {
+            Relaxation::Optimal { objective, .. } => {
+                Some(Self::objective_sign(model) * objective + model.objective().constant())
+            }
+            Relaxation::Infeasible | Relaxation::Unbounded => None,
+        };
+        Ok(bound)
+    }
+
+    fn objective_sign(model: &Model) -> f64 {
+        match model.sense() {
+            ModelSense::Minimize => 1.,
+            ModelSense::Maximize => -1.,
+        }
+    }
+
+    /// The objective coefficients of the equivalent minimization problem.
+    fn minimization_costs(model: &Model) -> Vec<f64> {
+        let sign = Self::objective_sign(model);
+        let mut costs = vec![0.; model.vars().len()];
+        for &(coeff, var) in model.objective().terms() {
+            costs[var.index()] += sign * coeff;
+        }
+        costs
+    }
+
+    /// Whether a node with the given bound can still contain a solution better than the
+    /// incumbent, taking the allowed relative gap into account.
+    fn may_improve(&self, bound: f64, incumbent: f64) -> bool {
+        let gap = self.limits.mip_gap().unwrap_or(0.) * incumbent.abs();
+        bound < incumbent - gap.max(INTEGRALITY_TOLERANCE)
+    }
+
+    /// The incumbent as a solution that is not optimal if the time ran out, otherwise the error.
+    fn interrupted(
+        model: &Model,
+        err: SchedularError,
+        incumbent: Option<(f64, Vec<f64>)>,
+    ) -> Result<Option<Solution>, SchedularError> {
+        match (err, incumbent) {
+            (SchedularError::TimeLimitReached, Some((_, values))) => {
+                Ok(Some(Solution::new(model, values).with_optimality(false)))
+            }
+            (err, _) => Err(err),
+        }
+    }
+
+    /// Searches depth first, exploring the branch closer to the relaxation's value first.
+    /// Returns the best solution, which is not optimal if the time ran out.
+    fn branch_and_bound(
+        &self,
+        model: &Model,
+        observer: &dyn Fn(SolverProgress),
+    ) -> Result<Option<Solution>, SchedularError> {
+        let costs = Self::minimization_costs(model);
+        let integers = model
+            .vars()
+            .iter()
+            .enumerate()
+            .filter(|(_, definition)| definition.var_type != VarType::Continuous)
+            .map(|(index, _)| index)
+            .collect_vec();
+        let mut root = Node {
+            lower: model.vars().iter().map(|var| var.lower).collect(),
+            upper: model.vars().iter().map(|var| var.upper).collect(),
+            bound: f64::NEG_INFINITY,
+        };
+        if root.lower.iter().any(|lower| !lower.is_finite()) {
+            return Err(SchedularError::InvalidConstraint(
+                "the native solver requires finite lower bounds".to_string(),
+            ));
+        }
+        for &var in integers.iter() {
+            root.lower[var] = root.lower[var].ceil();
+            root.upper[var] = root.upper[var].floor();
+        }
+
+        let mut stack = vec![root];
+        let mut incumbent: Option<(f64, Vec<f64>)> = None;
+        while let Some(node) = stack.pop() {
+            if let Err(err) = self.limits.check() {
+                return Self::interrupted(model, err, incumbent);
+            }
+            let best = incumbent.as_ref().map(|(objective, _)| *objective);
+            if best.is_some_and(|best| !self.may_improve(node.bound, best))
+                || node
+                    .lower
+                    .iter()
+                    .zip(node.upper.iter())
+                    .any(|(lower, upper)| lower > upper)
+            {
+                continue;
+            }
+            let relaxation =
+                match Self::relax(model, &costs, &node.lower, &node.upper, &self.limits) {
+                    Ok(relaxation) => relaxation,
+                    Err(err) => return Self::interrupted(model, err, incumbent),
+                };
+            let (objective, values) = match relaxation {
+                Relaxation::Optimal { objective, values } => (objective, values),
+                Relaxation::Infeasible => continue,
+                Relaxation::Unbounded => {
+                    return Err(SchedularError::InvalidConstraint(
+                        "the objective is unbounded".to_string(),
+                    ))
+                }
+            };
+            if best.is_some_and(|best| !self.may_improve(objective, best)) {
+                continue;
+            }
+
+            let fractional = integers
+                .iter()
+                .map(|&var| (var, values[var] - values[var].floor()))
+                .filter(|(_, fraction)| {
+                    *fraction > INTEGRALITY_TOLERANCE && *fraction < 1. - INTEGRALITY_TOLERANCE
+                })
+                .max_by(|(_, a), (_, b)| {
+                    (0.5 - (a - 0.5).abs()).total_cmp(&(0.5 - (b - 0.5).abs()))
+                });
+            let Some((var, fraction)) = fractional else {
+                let solution = Solution::new(model, values.clone());
+                observer(SolverProgress::Incumbent(
+                    solution.clone().with_optimality(false),
+                ));
+                let bound = stack
+                    .iter()
+                    .map(|node| node.bound)
+                    .fold(objective, f64::min);
+                observer(SolverProgress::Bound {
+                    objective: solution.objective_value(),
+                    bound: Self::objective_sign(model) * bound + model.objective().constant(),
+                });
+                incumbent = Some((objective, values));
+                continue;
+            };
+
+            let mut down = Node {
+                bound: objective,
+                ..node.clone()
+            };
+            down.upper[var] = values[var].floor();
+            let mut up = Node {
+                bound: objective,
+                ..node
+            };
+            up.lower[var] = values[var].ceil();
+            if fraction < 0.5 {
+                stack.extend([up, down]);
+            } else {
+                stack.extend([down, up]);
+            }
+        }
+        Ok(incumbent.map(|(_, values)| Solution::new(model, values)))
+    }
+}
+
+impl Solver for NativeSolver {
+    fn solve(
+        &self,
+        model: &Model,
+        max_num_solutions: usize,
+    ) -> Result<Vec<Solution>, SchedularError> {
+        self.solve_observed(model, max_num_solutions, &|_| {})
+    }
+
+    /// Only the search for the best solution reports its progress.
+    fn solve_observed(
+        &self,
+        model: &Model,
+        max_num_solutions: usize,
+        observer: &dyn Fn(SolverProgress),
+    ) -> Result<Vec<Solution>, SchedularError> {
+        let mut model = model.clone();
+        let mut solutions: Vec<Solution> = vec![];
+        while solutions.len() < max_num_solutions {
+            let result = if solutions.is_empty() {
+                self.branch_and_bound(&model, observer)
+            } else {
+                self.branch_and_bound(&model, &|_| {})
+            };
+            let solution = match result {
+                Ok(Some(solution)) => solution,
+                Ok(None) => break,
+                Err(SchedularError::TimeLimitReached) if !solutions.is_empty() => break,
+                Err(err) => return Err(err),
+            };
+            let optimal = solution.is_optimal();
+            model.add_no_good_cut(&solution);
+            solutions.push(solution);
+            if !optimal {
+                break;
+            }
+        }
+        Ok(solutions)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::schedular::{
+        solver::{ConstrSense, LinExpr, Model, ModelSense, SolveLimits, Solver, Var, VarType},
+        SchedularError,
+    };
+
+    use super::NativeSolver;
+
+    /// Knapsack with capacity 10 whose relaxation is fractional.
+    fn knapsack() -> (Model, Vec<Var>) {
+        let mut model = Model::new("knapsack");
+        let items = [(6., 30.), (5., 20.), (5., 19.), (4., 14.)]
+            .iter()
+            .enumerate()
+            .map(|(num, &(weight, value))| (model.add_binary(&format!("x{}", num)), weight, value))
+            .collect::<Vec<_>>();
+        let mut weight = LinExpr::new();
+        let mut value = LinExpr::new();
+        for &(var, item_weight, item_value) in items.iter() {
+            weight.add_term(item_weight, var);
+            value.add_term(item_value, var);
+        }
+        model.add_constr("capacity", weight, ConstrSense::Less, 10.);
+        model.set_objective(value, ModelSense::Maximize);
+        (model, items.iter().map(|&(var, _, _)| var).collect())
+    }
+
+    #[test]
+    fn test_top_solutions() {
+        let solutions = NativeSolver::default()
+            .solve(&knapsack().0, 3)
+            .expect("the knapsack is solvable");
+        let objectives = solutions
+            .iter()
+            .map(|solution| solution.objective_value())
+            .collect::<Vec<_>>();
+        assert_eq!(objectives, vec![44., 39., 34.]);
+        assert_eq!(solutions[0].values(), &[1., 0., 0., 1.]);
+        assert!(solutions.iter().all(|solution| solution.is_optimal()));
+    }
+
+    #[test]
+    fn test_continuous_and_equality_constraints() {
+        // minimize the deviation of 3x + 4y from 10 with at most one of x and y
+        let mut model = Model::new("deviation");
+        let x = model.add_binary("x");
+        let y = model.add_binary("y");
+        let below = model.add_var("below", VarType::Continuous, 0., f64::INFINITY);
+        let above = model.add_var("above", VarType::Continuous, 0., f64::INFINITY);
+        let mut amount = LinExpr::new();
+        amount.add_term(3., x);
+        amount.add_term(4., y);
+        amount.add_term(1., below);
+        amount.add_term(-1., above);
+        model.add_constr("target", amount, ConstrSense::Equal, 10.);
+        let mut at_most_one = LinExpr::new();
+        at_most_one.add_term(1., x);
+        at_most_one.add_term(1., y);
+        model.add_constr("at_most_one", at_most_one, ConstrSense::Less, 1.);
+        let mut deviation = LinExpr::new();
+        deviation.add_term(1., below);
+        deviation.add_term(1., above);
+        model.set_objective(deviation, ModelSense::Minimize);
+
+        let solutions = NativeSolver::default().solve(&model, 1).unwrap();
+        assert_eq!(solutions[0].value(y), 1.);
+        assert!((solutions[0].objective_value() - 6.).abs() < 1e-6);
+    }
+
+    /// Compares the best objectives with all feasible assignments of small random models.
+    #[test]
+    fn test_matches_enumeration() {
+        let mut seed: u64 = 7;
+        let mut random = |range: i64| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((seed >> 33) as i64 % range) as f64
+        };
+        for _ in 0..30 {
+            let mut model = Model::new("random");
+            let vars = (0..8)
+                .map(|num| model.add_binary(&format!("x{}", num)))
+                .collect::<Vec<_>>();
+            for num in 0..4 {
+                let mut expr = LinExpr::new();
+                for &var in vars.iter() {
+                    expr.add_term(random(7) - 3., var);
+                }
+                let sense = [ConstrSense::Less, ConstrSense::Greater, ConstrSense::Equal]
+                    [random(3) as usize];
+                let rhs = match sense {
+                    ConstrSense::Less => random(5) + 1.,
+                    ConstrSense::Greater => -random(5) - 1.,
+                    ConstrSense::Equal => random(3) - 1.,
+                };
+                model.add_constr(&format!("c{}", num), expr, sense, rhs);
+            }
+            let mut objective = LinExpr::new();
+            for &var in vars.iter() {
+                objective.add_term(random(11) - 5., var);
+            }
+            model.set_objective(objective.clone(), ModelSense::Maximize);
+
+            let mut expected = (0..1 << vars.len())
+                .map(|mask| {
+                    (0..vars.len())
+                        .map(|bit| ((mask >> bit) & 1) as f64)
+                        .collect()
+                })
+                .filter(|values: &Vec<f64>| model.is_feasible(values))
+                .map(|values| objective.evaluate(&values))
+                .collect::<Vec<_>>();
+            expected.sort_by(|a, b| b.total_cmp(a));
+            expected.truncate(3);
+            let found = NativeSolver::default()
+                .solve(&model, 3)
+                .expect("binary models are bounded")
+                .iter()
+                .map(|solution| solution.objective_value())
+                .collect::<Vec<_>>();
+            assert_eq!(found, expected);
+        }
+    }
+
+    #[test]
+    fn test_infeasible_and_limits() {
+        let (mut model, items) = knapsack();
+        model.add_constr(
+            "three_items",
+            LinExpr::sum(&items),
+            ConstrSense::Greater,
+            3.,
+        );
+        assert!(NativeSolver::default().solve(&model, 1).unwrap().is_empty());
+
+        let limits = SolveLimits::new(Some(Duration::ZERO), None);
+        assert!(matches!(
+            NativeSolver::new(limits.clone()).solve(&knapsack().0, 1),
+            Err(SchedularError::TimeLimitReached)
+        ));
+        // the simplex itself stops at the limit, not only the branch and bound loop
+        assert!(matches!(
+            NativeSolver::relaxation_bound(&knapsack().0, &limits),
+            Err(SchedularError::TimeLimitReached)
+        ));
+        assert_eq!(
+            NativeSolver::relaxation_bound(&knapsack().0, &SolveLimits::default()).unwrap(),
+            Some(46.)
+        );
+    }
+}
@@ -0,0 +1,395 @@
+use itertools::Itertools;
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::scraper::appointment::SingleAppointment;
+
+use super::{course_selection::CourseSelection, settings::ConstraintSettings, WEEKDAYS};
+
+const TOLERANCE: f64 = 1e-6;
+
+/// A hard constraint a schedule does not satisfy.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Breach {
+    /// The setting that is not satisfied, `overlap` and `one_variant` for the rules every
+    /// schedule has to follow
+    pub constraint: String,
+    pub message: String,
+}
+
+impl Breach {
+    fn new(constraint: &str, message: String) -> Self {
+        Self {
+            constraint: constraint.to_owned(),
+            message,
+        }
+    }
+}
+
+/// Checks the course selections of a schedule against the constraint settings without
+/// the optimization model, so that solver results and hand-built schedules can be
+/// verified alike. Settings that are soft constraints of the request are not checked.
+pub fn verify(
+    course_selections: &[CourseSelection],
+    constraints: &ConstraintSettings,
+) -> Vec<Breach> {
+    let soft = constraints.soft_constraints.clone().unwrap_or_default();
+    let is_hard = |setting: &str| !soft.contains_key(setting);
+    let appointments = course_selections
+        .iter()
+        .flat_map(|selection| {
+            selection
+                .appointments
+                .iter()
+                .map(move |appointment| (selection, appointment))
+        })
+        .collect_vec();
+
+    let mut breaches = overlaps(&appointments);
+    for (subject, variants) in course_selections
+        .iter()
+        .into_group_map_by(|selection| &selection.subject)
+        .into_iter()
+        .sorted_by_key(|(subject, _)| *subject)
+    {
+        if variants.len() > 1 {
+            breaches.push(Breach::new(
+                "one_variant",
+                format!("{} is selected {} times", subject, variants.len()),
+            ));
+        }
+    }
+
+    let total_ects: f64 = course_selections
+        .iter()
+        .map(|selection| selection.ects)
+        .sum();
+    if let Some(min_ects) = constraints.min_num_ects.filter(|_| is_hard("min_num_ects")) {
+        if total_ects < min_ects as f64 - TOLERANCE {
+            breaches.push(Breach::new(
+                "min_num_ects",
+                format!("{} ECTS are less than {}", total_ects, min_ects),
+            ));
+        }
+    }
+    if let Some(max_ects) = constraints.max_num_ects.filter(|_| is_hard("max_num_ects")) {
+        if total_ects > max_ects as f64 + TOLERANCE {
+            breaches.push(Breach::new(
+                "max_num_ects",
+                format!("{} ECTS are more than {}", total_ects, max_ects),
+            ));
+        }
+    }
+
+    let weekdays = course_selections
+        .iter()
+        .flat_map(|selection| selection.weekdays())
+        .unique()
+        .collect_vec();
+    if let Some(max_days) = constraints.max_num_days.filter(|_| is_hard("max_num_days")) {
+        if weekdays.len() as i32 > max_days {
+            breaches.push(Breach::new(
+                "max_num_days",
+                format!(
+                    "courses take place on {} days instead of at most {}",
+                    weekdays.len(),
+                    max_days
+                ),
+            ));
+        }
+    }
+    if let Some(min_free_days) = constraints
+        .min_free_days
+        .filter(|_| is_hard("min_free_days"))
+    {
+        let free_days = WEEKDAYS
+            .iter()
+            .filter(|weekday| !weekdays.iter().any(|day| day == *weekday))
+            .count();
+        if (free_days as i32) < min_free_days {
+            breaches.push(Breach::new(
+                "min_free_days",
+                format!(
+                    "only {} weekdays are free instead of {}",
+                    free_days, min_free_days
+                ),
+            ));
+        }
+    }
+    if let Some(max_hours) = constraints
+        .max_hours_per_day
+        .filter(|_| is_hard("max_hours_per_day"))
+    {
+        let minutes_per_day = appointments
+            .iter()
+            .map(|(_, appointment)| (&appointment.weekday, duration_minutes(appointment)))
+            .into_grouping_map()
+            .sum();
+        for (weekday, minutes) in minutes_per_day.into_iter().sorted() {
+            if minutes as f64 > max_hours * 60. + TOLERANCE {
+                breaches.push(Breach::new(
+                    "max_hours_per_day",
+                    format!(
+                        "{} has {} minutes of courses, more than {} hours",
+                        weekday, minutes, max_hours
+                    ),
+                ));
+            }
+        }
+    }
+
+    if let Some(caps) = constraints
+        .max_courses_per_faculty
+        .as_ref()
+        .filter(|_| is_hard("max_courses_per_faculty"))
+    {
+        let courses_per_faculty: HashMap<&String, usize> = course_selections
+            .iter()
+            .map(|selection| &selection.faculty)
+            .counts();
+        for (faculty, max_courses) in caps.iter() {
+            let courses = courses_per_faculty.get(faculty).copied().unwrap_or(0);
+            if courses as i32 > *max_courses {
+                breaches.push(Breach::new(
+                    "max_courses_per_faculty",
+                    format!(
+                        "{} courses of {} instead of at most {}",
+                        courses, faculty, max_courses
+                    ),
+                ));
+            }
+        }
+    }
+
+    if is_hard("blocked_slots") {
+        for slot in constraints.blocked_slots.iter().flatten() {
+            for (selection, appointment) in appointments.iter() {
+                if appointment.weekday == slot.weekday
+                    && appointment.from < slot.to
+                    && slot.from < appointment.to
+                {
+                    breaches.push(Breach::new(
+                        "blocked_slots",
+                        format!(
+                            "{} on {} {}-{} overlaps the blocked slot {}-{}",
+                            selection.subject,
+                            appointment.weekday,
+                            appointment.from.format("%H:%M"),
+                            appointment.to.format("%H:%M"),
+                            slot.from.format("%H:%M"),
+                            slot.to.format("%H:%M")
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+    if is_hard("day_windows") {
+        for window in constraints.day_windows.iter().flatten() {
+            for (selection, appointment) in appointments.iter() {
+                if window
+                    .weekday
+                    .as_ref()
+                    .is_some_and(|weekday| *weekday != appointment.weekday)
+                {
+                    continue;
+                }
+                let too_early = window
+                    .earliest_start
+                    .is_some_and(|earliest| appointment.from < earliest);
+                let too_late = window
+                    .latest_end
+                    .is_some_and(|latest| appointment.to > latest);
+                if too_early || too_late {
+                    breaches.push(Breach::new(
+                        "day_windows",
+                        format!(
+                            "{} on {} {}-{} is outside of the day window",
+                            selection.subject,
+                            appointment.weekday,
+                            appointment.from.format("%H:%M"),
+                            appointment.to.format("%H:%M")
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    if is_hard("locked_selections") {
+        for id in constraints.locked_selections.iter().flatten() {
+            if !course_selections
+                .iter()
+                .any(|selection| selection.id == *id)
+            {
+                breaches.push(Breach::new(
+                    "locked_selections",
+                    format!("locked selection {} is missing", id),
+                ));
+            }
+        }
+    }
+    if is_hard("banned_selections") {
+        for id in constraints.banned_selections.iter().flatten() {
+            if course_selections
+                .iter()
+                .any(|selection| selection.id == *id)
+            {
+                breaches.push(Breach::new(
+                    "banned_selections",
+                    format!("banned selection {} is selected", id),
+                ));
+            }
+        }
+    }
+    breaches
+}
+
+fn duration_minutes(appointment: &SingleAppointment) -> i64 {
+    (appointment.to - appointment.from).num_minutes()
+}
+
+/// Every pair of appointments on the same weekday that overlap by more than touching.
+fn overlaps(appointments: &[(&CourseSelection, &SingleAppointment)]) -> Vec<Breach> {
+    appointments
+        .iter()
+        .tuple_combinations()
+        .filter(|((_, a), (_, b))| a.weekday == b.weekday && a.from < b.to && b.from < a.to)
+        .map(|((first, a), (second, b))| {
+            Breach::new(
+                "overlap",
+                format!(
+                    "{} {}-{} and {} {}-{} overlap on {}",
+                    first.subject,
+                    a.from.format("%H:%M"),
+                    a.to.format("%H:%M"),
+                    second.subject,
+                    b.from.format("%H:%M"),
+                    b.to.format("%H:%M"),
+                    a.weekday
+                ),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use chrono::NaiveTime;
+
+    use crate::schedular::{
+        course_selection::CourseSelection,
+        settings::{ConstraintSettings, DayWindow, TimeSlot},
+    };
+    use crate::scraper::lecture::Lecture;
+
+    use super::verify;
+
+    fn time(text: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(text, "%H:%M").unwrap()
+    }
+
+    fn selections(lectures: Vec<Lecture>) -> Vec<CourseSelection> {
+        CourseSelection::build_from_lectures(lectures)
+    }
+
+    fn constraints_of(breaches: &[super::Breach]) -> Vec<&str> {
+        breaches
+            .iter()
+            .map(|breach| breach.constraint.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_schedule() {
+        let schedule = selections(vec![
+            Lecture::new("8:00", "10:00", "Monday", "VO", "MA0001", "First", 5.),
+            Lecture::new("10:00", "12:00", "Monday", "VO", "MA0002", "Second", 5.),
+        ]);
+        let constraints = ConstraintSettings {
+            min_num_ects: Some(10),
+            max_num_days: Some(1),
+            max_hours_per_day: Some(4.),
+            ..Default::default()
+        };
+        assert!(verify(&schedule, &constraints).is_empty());
+    }
+
+    #[test]
+    fn test_overlaps_and_variants() {
+        let schedule = selections(vec![
+            Lecture::new("8:00", "10:00", "Monday", "VO", "MA0001", "First", 5.),
+            Lecture::new("9:59", "11:00", "Monday", "VO", "MA0002", "Second", 5.),
+            Lecture::new("8:00", "10:00", "Tuesday", "UE", "MA0003", "Third", 3.),
+            Lecture::new("8:00", "10:00", "Wednesday", "UE", "MA0003", "Third", 3.),
+        ]);
+        let breaches = verify(&schedule, &ConstraintSettings::default());
+        assert_eq!(constraints_of(&breaches), vec!["overlap", "one_variant"]);
+        assert!(breaches[0].message.contains("MA0001"));
+        assert!(breaches[1].message.starts_with("MA0003"));
+    }
+
+    #[test]
+    fn test_constraint_settings() {
+        let mut schedule = selections(vec![
+            Lecture::new("8:00", "10:00", "Monday", "VO", "MA0001", "First", 5.),
+            Lecture::new("12:00", "16:00", "Tuesday", "VO", "MA0002", "Second", 5.),
+        ]);
+        for selection in schedule.iter_mut() {
+            selection.faculty = "MATH".to_string();
+        }
+        let locked = "IN0001/1/".to_string();
+        let banned = schedule[0].id.clone();
+        let constraints = ConstraintSettings {
+            min_num_ects: Some(12),
+            max_num_ects: Some(8),
+            max_num_days: Some(1),
+            min_free_days: Some(4),
+            max_hours_per_day: Some(3.),
+            max_courses_per_faculty: Some(vec![("MATH".to_string(), 1)]),
+            blocked_slots: Some(vec![TimeSlot {
+                weekday: "Monday".to_string(),
+                from: time("09:00"),
+                to: time("09:30"),
+            }]),
+            day_windows: Some(vec![DayWindow {
+                weekday: None,
+                earliest_start: None,
+                latest_end: Some(time("15:00")),
+            }]),
+            locked_selections: Some(vec![locked]),
+            banned_selections: Some(vec![banned]),
+            ..Default::default()
+        };
+        let breaches = verify(&schedule, &constraints);
+        assert_eq!(
+            constraints_of(&breaches),
+            vec![
+                "min_num_ects",
+                "max_num_ects",
+                "max_num_days",
+                "min_free_days",
+                "max_hours_per_day",
+                "max_courses_per_faculty",
+                "blocked_slots",
+                "day_windows",
+                "locked_selections",
+                "banned_selections",
+            ]
+        );
+
+        let soft = ConstraintSettings {
+            soft_constraints: Some(HashMap::from([
+                ("min_num_ects".to_string(), 1.),
+                ("max_num_ects".to_string(), 1.),
+            ])),
+            ..constraints
+        };
+        let breaches = verify(&schedule, &soft);
+        assert!(!constraints_of(&breaches).contains(&"min_num_ects"));
+        assert!(!constraints_of(&breaches).contains(&"max_num_ects"));
+        assert_eq!(breaches.len(), 8);
+    }
+}
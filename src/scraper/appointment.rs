@@ -1,5 +1,5 @@
 use roxmltree::Document;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::env;
 use std::str::FromStr;
 
@@ -9,11 +9,14 @@ use super::tum_xml_node::TumXmlNode;
 use super::ScraperError;
 use super::TumXmlError;
 
-#[derive(Debug, Hash, PartialEq, Eq, Clone, Serialize)]
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct SingleAppointment {
+    #[serde(default)]
+    pub lecture_id: String,
     pub weekday: String,
     pub from: NaiveTime,
     pub to: NaiveTime,
+    #[serde(default)]
     pub course_type: String,
 }
 
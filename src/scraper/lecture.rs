@@ -174,6 +174,7 @@ impl Lecture {
     }
     pub fn appointment(&self) -> SingleAppointment {
         SingleAppointment {
+            lecture_id: self.id.clone(),
             from: self.start_time,
             to: self.end_time,
             weekday: self.weekday.clone(),